@@ -33,7 +33,8 @@ fn main() {
         let mut last_frame = Instant::now();
 
         let image_bytes = include_bytes!("nori.gif");
-        let image = CacheableImage::from_encoded(image_bytes);
+        let image = AnimatedImage::from_encoded(image_bytes).unwrap();
+        let start = Instant::now();
 
         let svg_bytes = include_bytes!("home.svg");
         let svg = CacheableSvg::new_cloned(svg_bytes);
@@ -46,16 +47,15 @@ fn main() {
 
         let const_objects = Vec::from([
             // Using a specific font
-            Object::text(70, 100, "t r a n s p a r e n c y".to_string(), Some("JetBrains Mono".to_string()), 22.0, 0x88AAFFFF),
-            Object::rectangle(100, 100, 200, 300,  0xFF9999DD, Some(Rounding::new_uniform(16))),
-            Object::svg(20, 200, 80, 40, svg.clone(), 0xFFAAFFFF),
+            Object::text(70.0, 100.0, "t r a n s p a r e n c y".to_string(), Some("JetBrains Mono".to_string()), 22.0, 0x88AAFFFF),
+            Object::rectangle(100.0, 100.0, 200.0, 300.0,  0xFF9999DD, Some(Rounding::new_uniform(16.0))),
+            Object::svg(20.0, 200.0, 80.0, 40.0, svg.clone(), 0xFFAAFFFF),
             // Using the default/fallback font
-            Object::text(20, 20,  "Hello, world!".to_string(), None, 30.0, 0xFFFFFFFF),
-            Object::text(100, 400,  "TeXt!!1".to_string(), None, 100.0, 0xFFFFFFFF),
-            Object::image(400, 10, image.dimensions().width / 4, image.dimensions().height / 4, image.clone()),
-            Object::text(30, 550 + paragraph.height() as i32, paragraph.height().to_string(), None, 20.0, 0xFFFFFFFF),
+            Object::text(20.0, 20.0,  "Hello, world!".to_string(), None, 30.0, 0xFFFFFFFF),
+            Object::text(100.0, 400.0,  "TeXt!!1".to_string(), None, 100.0, 0xFFFFFFFF),
+            Object::text(30.0, 550.0 + paragraph.height(), paragraph.height().to_string(), None, 20.0, 0xFFFFFFFF),
             // For multiline text
-            Object::paragraph(30, 550, paragraph.clone())
+            Object::paragraph(30.0, 550.0, paragraph.clone())
         ]);
 
         let backend = &backend;
@@ -87,7 +87,8 @@ fn main() {
                 &backend.data(),
                 const_objects.iter()
                 .chain([
-                    &Object::text(20, 55, frame_time, Some("JetBrains Mono".to_string()), 16.0, 0xFFFFFFFF)
+                    &Object::text(20.0, 55.0, frame_time, Some("JetBrains Mono".to_string()), 16.0, 0xFFFFFFFF),
+                    &Object::animated_image(400.0, 10.0, image.dimensions().width as f32 / 4.0, image.dimensions().height as f32 / 4.0, &image, start.elapsed())
                 ])
                 .collect()
             ).unwrap();
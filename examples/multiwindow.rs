@@ -51,7 +51,7 @@ fn main() {
                 renderer.render(
                     window,
                     &backend.data(),
-                    vec![&Object::text(10, 10, index.to_string(), None, 50.0, 0xFFFFFFFF)]
+                    vec![&Object::text(10.0, 10.0, index.to_string(), None, 50.0, 0xFFFFFFFF)]
                 ).unwrap();
             }
         });
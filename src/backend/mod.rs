@@ -15,6 +15,10 @@ use self::{errors::BackendError, windowing::window::{Window, WindowDetails}};
 use windowing::glfw::*;
 #[cfg(feature = "b-winit")]
 use windowing::winit::*;
+#[cfg(feature = "b-headless")]
+use log::warn;
+#[cfg(feature = "b-headless")]
+use self::{events::WindowEvent, windowing::{headless::*, window::WindowId}};
 
 pub mod renderer_data;
 pub mod windowing;
@@ -32,6 +36,8 @@ pub enum BackendType {
     Winit,
     #[cfg(feature = "b-glfw")]
     Glfw,
+    #[cfg(feature = "b-headless")]
+    Headless,
 }
 
 impl Default for BackendType {
@@ -65,6 +71,13 @@ impl Backend<()> {
     pub fn create(callback: impl FnOnce(Backend<()>) + Copy + Send + 'static) -> BResult<()> {
         Self::create_custom(callback)
     }
+
+    /// Creates a backend with virtual windows, without taking over the current thread.  
+    /// All windows created by it report the given scale factor.
+    #[cfg(feature = "b-headless")]
+    pub fn headless(scale: f32) -> Backend<()> {
+        Self::headless_custom(scale)
+    }
 }
 
 impl<T> Backend<T> {
@@ -79,6 +92,24 @@ impl<T> Backend<T> {
         })
     }
     
+    #[cfg(feature = "b-headless")]
+    pub fn headless_custom(scale: f32) -> Backend<T> {
+        Self {
+            window_backend: WindowBackend::Headless(HeadlessBackend::new(scale)),
+            renderer_data: RefCell::new(RendererData::placeholder())
+        }
+    }
+
+    /// Pushes a synthetic window event, which will be returned by the next `flush_events` or `subscribe_events`.  
+    /// Only supported by the headless backend.
+    #[cfg(feature = "b-headless")]
+    pub fn push_window_event(&self, window_id: &WindowId, event: WindowEvent) {
+        match &self.window_backend {
+            WindowBackend::Headless(headless) => headless.push_event(window_id, event),
+            _ => warn!("Window events can only be pushed to headless backends!")
+        }
+    }
+    
     pub fn data(&self) -> Ref<RendererData> {
         self.renderer_data.borrow()
     }
@@ -1,10 +1,10 @@
-use std::num::NonZeroU32;
+use std::{num::NonZeroU32, sync::Arc};
 
 use enum_dispatch::enum_dispatch;
 use placeholder::PlaceholderRendererData;
 use strum::EnumTryAs;
 
-use crate::{renderer::{images::CacheableImage, svgs::CacheableSvg, text::{Paragraph, TextOptions}}, types::WindowId};
+use crate::{renderer::{errors::RendererError, hit_test::{self, Hit}, images::CacheableImage, objects::{Object, Rect}, svgs::CacheableSvg, text::{FontMetrics, Paragraph, TextMetrics, TextOptions, TextSpan}, RResult, RendererType}, structs::{Dimensions, Position}, types::WindowId};
 
 
 pub mod placeholder;
@@ -14,6 +14,8 @@ pub mod wgpu;
 pub mod vello;
 #[cfg(feature = "r-skia")]
 pub mod skia;
+#[cfg(feature = "r-tiny-skia")]
+pub mod tiny_skia;
 
 
 #[derive(Debug, EnumTryAs)]
@@ -25,7 +27,9 @@ pub enum RendererData {
     #[cfg(feature = "r-vello")]
     Vello(vello::VelloRendererData),
     #[cfg(feature = "r-skia")]
-    Skia(skia::SkiaRendererData)
+    Skia(skia::SkiaRendererData),
+    #[cfg(feature = "r-tiny-skia")]
+    TinySkia(tiny_skia::TinySkiaRendererData)
 }
 
 impl RendererData {
@@ -33,11 +37,95 @@ impl RendererData {
         RendererData::Placeholder(PlaceholderRendererData::new())
     }
 
+    /// Creates the data for the given renderer type directly, without creating a renderer for a window first.  
+    /// Mostly useful for offscreen rendering with `RendererData::render_offscreen`.
+    pub fn create(typ: RendererType) -> RResult<Self> {
+        Ok(match typ {
+            #[cfg(feature = "r-wgpu")]
+            RendererType::Wgpu => return Err(RendererError::NoRenderer),
+            #[cfg(feature = "r-vello")]
+            RendererType::Vello => RendererData::Vello(vello::VelloRendererData::new(::vello::util::RenderContext::new())),
+            #[cfg(feature = "r-skia")]
+            RendererType::Skia => RendererData::Skia(skia::SkiaRendererData::new()),
+            #[cfg(feature = "r-tiny-skia")]
+            RendererType::TinySkia => RendererData::TinySkia(tiny_skia::TinySkiaRendererData::new()),
+        })
+    }
+
+    /// Renders the objects into a `CacheableImage` of the given (physical) dimensions, without needing a `Window`.  
+    /// `scale` works the same as a window's scale, the background is transparent.
+    pub fn render_offscreen(&self, objects: Vec<&Object>, dimensions: Dimensions<u32>, scale: f32) -> RResult<CacheableImage> {
+        match self {
+            RendererData::Placeholder(_) => Err(RendererError::Uninitialized),
+            #[cfg(feature = "r-wgpu")]
+            RendererData::Wgpu(_) => Err(RendererError::NoRenderer),
+            #[cfg(feature = "r-vello")]
+            RendererData::Vello(data) => crate::renderer::vello::render_offscreen(data, objects, dimensions, scale),
+            #[cfg(feature = "r-skia")]
+            RendererData::Skia(data) => crate::renderer::skia::render_offscreen(data, objects, dimensions, scale),
+            #[cfg(feature = "r-tiny-skia")]
+            RendererData::TinySkia(data) => crate::renderer::tiny_skia::render_offscreen(data, objects, dimensions, scale),
+        }
+    }
+
+    /// Finds the topmost object under the position, going through the objects in the order they are drawn with.  
+    /// The position is logical like the objects, scale cursor positions with `WindowEvent::scale_with` first.
+    /// Returns the index of the top level object and the id of the innermost `Object::Tagged` that was hit.
+    pub fn hit_test(&self, objects: &[&Object], position: &Position<f64>) -> Option<Hit> {
+        hit_test::hit_test(
+            objects, position.x as f32, position.y as f32,
+            &|text, font, size| self.measure_single_line(text, font, size),
+            &|svg| self.svg_size(svg)
+        )
+    }
+
+    /// The logical bounds of the object, like `Object::bounds`, but measuring `Object::Text` the way this renderer draws it.
+    pub(crate) fn object_bounds(&self, object: &Object) -> Option<Rect> {
+        object.bounds_with(&|text, font, size| self.measure_single_line(text, font, size))
+    }
+
+    /// The size of the SVG as (width, height), or `None` if it can't be parsed
+    fn svg_size(&self, svg: &CacheableSvg) -> Option<(f32, f32)> {
+        match self {
+            RendererData::Placeholder(_) => None,
+            #[cfg(feature = "r-wgpu")]
+            RendererData::Wgpu(_) => None,
+            #[cfg(feature = "r-vello")]
+            RendererData::Vello(_) => crate::renderer::vello::adapter::svg_size(svg),
+            #[cfg(feature = "r-skia")]
+            RendererData::Skia(data) => crate::renderer::skia::adapter::svg_size(data, svg),
+            #[cfg(feature = "r-tiny-skia")]
+            RendererData::TinySkia(data) => crate::renderer::tiny_skia::adapter::svg_size(data, svg),
+        }
+    }
+
+    /// Measures the text of an `Object::Text` as (width, height), or `None` if there is no such font
+    fn measure_single_line(&self, text: &str, font: &Option<String>, size: f32) -> Option<(f32, f32)> {
+        match self {
+            RendererData::Placeholder(_) => None,
+            #[cfg(feature = "r-wgpu")]
+            RendererData::Wgpu(_) => None,
+            #[cfg(feature = "r-vello")]
+            RendererData::Vello(data) => crate::renderer::vello::adapter::measure_text(data, text, font, size),
+            #[cfg(feature = "r-skia")]
+            RendererData::Skia(data) => crate::renderer::skia::adapter::measure_text(data, text, font, size),
+            #[cfg(feature = "r-tiny-skia")]
+            RendererData::TinySkia(data) => crate::renderer::tiny_skia::adapter::measure_text(data, text, font, size),
+        }
+    }
+
     pub fn create_paragraph(&self, text: String, width: u32, max_height: Option<u32>, options: TextOptions) -> Paragraph {
         let max_h = max_height.and_then(NonZeroU32::new);
         
         Paragraph::new(self, text, width, max_h, options)
     }
+
+    /// Creates a paragraph from differently styled spans of text, see `RichText`
+    pub fn create_rich_paragraph(&self, spans: Vec<TextSpan>, width: u32, max_height: Option<u32>, options: TextOptions) -> Paragraph {
+        let max_h = max_height.and_then(NonZeroU32::new);
+
+        Paragraph::new_rich(self, spans, width, max_h, options)
+    }
 }
 
 
@@ -60,4 +148,22 @@ pub trait RendererDataTrait {
     // fn transform_with(&self, renderer: &Renderer) -> Option<RendererData>;
     /// Called when a Window is closed, to remove associated data from caches
     fn remove_window_data(&self, window_id: &WindowId);
+    /// All registered fonts with their bytes, in no particular order
+    fn fonts(&self) -> Vec<RegisteredFont>;
+    /// Measures text without laying out a paragraph, only breaking lines at `\n`.
+    /// Returns `None` if no font is registered.
+    fn measure_text(&self, text: &str, options: &TextOptions) -> Option<TextMetrics>;
+    /// The metrics of a registered font at the given size, or of the default font if `font` is `None`.
+    /// Returns `None` if no font is registered.
+    fn font_metrics(&self, font: &Option<String>, size: f32) -> Option<FontMetrics>;
+}
+
+/// A font as it was registered with `RendererDataTrait::register_font`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegisteredFont {
+    pub alias: String,
+    pub bytes: Arc<[u8]>,
+    /// Whether this is the default font
+    pub default: bool
 }
\ No newline at end of file
@@ -1,9 +1,9 @@
-use std::cell::{Cell, RefCell};
+use std::{cell::{Cell, RefCell}, sync::Arc};
 
 
-use crate::{renderer::{images::CacheableImage, svgs::CacheableSvg}, types::WindowId};
+use crate::{renderer::{images::CacheableImage, svgs::CacheableSvg, text::{FontMetrics, TextMetrics, TextOptions}}, types::WindowId};
 
-use super::RendererDataTrait;
+use super::{RegisteredFont, RendererDataTrait};
 
 
 #[derive(Debug)]
@@ -104,4 +104,23 @@ impl RendererDataTrait for PlaceholderRendererData {
     fn remove_window_data(&self, _window_id: &WindowId) {
         // Nothing needed here
     }
+
+    fn fonts(&self) -> Vec<RegisteredFont> {
+        let default_index = self.default_index.get();
+
+        self.fonts.borrow().iter().enumerate().map(|(index, (alias, bytes))| RegisteredFont {
+            alias: alias.clone(),
+            bytes: Arc::from(bytes.as_slice()),
+            default: index as isize == default_index
+        }).collect()
+    }
+
+    // Fonts are only loaded by the actual renderer data
+    fn measure_text(&self, _text: &str, _options: &TextOptions) -> Option<TextMetrics> {
+        None
+    }
+
+    fn font_metrics(&self, _font: &Option<String>, _size: f32) -> Option<FontMetrics> {
+        None
+    }
 }
\ No newline at end of file
@@ -1,16 +1,18 @@
-use std::{cell::RefCell, collections::HashMap};
+use std::{cell::RefCell, collections::HashMap, sync::Arc};
 
-use skia_safe::{svg::Dom, textlayout::{FontCollection, TypefaceFontProvider}, wrapper::PointerWrapper, Canvas, FontMgr, Typeface};
+use skia_safe::{svg::Dom, textlayout::{FontCollection, TypefaceFontProvider}, wrapper::PointerWrapper, Canvas, Font, FontMgr, Typeface};
 use uuid::Uuid;
 
-use crate::{renderer::{images::CacheableImage, skia::adapter, svgs::CacheableSvg}, types::WindowId};
+use crate::{renderer::{images::CacheableImage, skia::{adapter, text}, svgs::CacheableSvg, text::{FontMetrics, TextMetrics, TextOptions}}, types::WindowId};
 
-use super::RendererDataTrait;
+use super::{RegisteredFont, RendererDataTrait};
 
 
 #[derive(Debug)]
 pub struct SkiaRendererData {
     pub font_map: RefCell<HashMap<String, Typeface>>,
+    /// The bytes of every registered font, as Skia doesn't keep them around
+    pub font_bytes: RefCell<HashMap<String, Arc<[u8]>>>,
     pub font_mgr: FontMgr,
     pub font_collection: FontCollection,
     pub font_provider: TypefaceFontProvider,
@@ -43,6 +45,7 @@ impl SkiaRendererData {
 
         Self {
             font_map: RefCell::new(HashMap::new()),
+            font_bytes: RefCell::new(HashMap::new()),
             font_mgr,
             font_collection,
             font_provider,
@@ -105,6 +108,21 @@ impl SkiaRendererData {
         }
     }
 
+    pub(crate) fn get_or_load_svg_dom(&self, svg: &CacheableSvg) -> Dom {
+        let mut cache = self.svg_cache.borrow_mut();
+
+        match cache.get(svg.uuid()) {
+            Some(SkiaCachedSvg::Dom(dom)) => dom.clone(),
+            Some(SkiaCachedSvg::Surface(svg_with_surface)) => svg_with_surface.dom.clone(),
+            None => {
+                let dom = adapter::svg_to_skia(svg, self.get_font_mgr());
+
+                cache.insert(*svg.uuid(), SkiaCachedSvg::Dom(dom.clone()));
+                dom
+            }
+        }
+    }
+
     pub fn get_font_mgr(&self) -> FontMgr {
         self.font_mgr.clone()
     }
@@ -125,6 +143,7 @@ impl RendererDataTrait for SkiaRendererData {
             alias.to_string(),
             typeface
         );
+        self.font_bytes.borrow_mut().insert(alias.to_string(), Arc::from(bytes));
     }
 
     fn register_default_font(&self, bytes: &[u8], alias: &str) {
@@ -137,6 +156,7 @@ impl RendererDataTrait for SkiaRendererData {
             alias.to_string(),
             typeface
         );
+        self.font_bytes.borrow_mut().insert(alias.to_string(), Arc::from(bytes));
     }
 
     fn load_image(&self, image: &CacheableImage) {
@@ -175,6 +195,26 @@ impl RendererDataTrait for SkiaRendererData {
             svgs.remove(&uuid);
         }
     }
+
+    fn fonts(&self) -> Vec<RegisteredFont> {
+        let fonts = self.font_map.borrow();
+        let default_id = self.default_font.borrow().as_ref().map(Typeface::unique_id);
+
+        self.font_bytes.borrow().iter().map(|(alias, bytes)| RegisteredFont {
+            alias: alias.clone(),
+            bytes: bytes.clone(),
+            default: default_id.is_some() && fonts.get(alias).map(Typeface::unique_id) == default_id
+        }).collect()
+    }
+
+    fn measure_text(&self, text: &str, options: &TextOptions) -> Option<TextMetrics> {
+        text::measure_text(self, text, options)
+    }
+
+    fn font_metrics(&self, font: &Option<String>, size: f32) -> Option<FontMetrics> {
+        let font = Font::from_typeface(self.get_font(font)?, size);
+        Some(text::font_metrics(&font))
+    }
 }
 
 
@@ -0,0 +1,155 @@
+use std::{cell::RefCell, collections::HashMap, fmt::Debug, rc::Rc, sync::Arc};
+
+use log::warn;
+use resvg::usvg;
+use skrifa::{instance::Location, raw::FileRef, FontRef};
+use tiny_skia::Pixmap;
+use uuid::Uuid;
+
+use crate::{renderer::{layout, tiny_skia::adapter}, types::{CacheableImage, CacheableSvg, FontMetrics, TextMetrics, TextOptions, WindowId}};
+
+use super::{RegisteredFont, RendererDataTrait};
+
+
+pub struct TinySkiaRendererData {
+    pub fonts: RefCell<HashMap<String, TinySkiaFont>>,
+    pub default_font_alias: RefCell<String>,
+    pub image_cache: RefCell<HashMap<Uuid, Rc<Pixmap>>>,
+    pub svg_cache: RefCell<HashMap<Uuid, Rc<usvg::Tree>>>
+}
+
+#[derive(Clone)]
+pub struct TinySkiaFont {
+    pub data: Arc<[u8]>,
+    pub font_ref: FontRef<'static>
+}
+
+impl Default for TinySkiaRendererData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TinySkiaRendererData {
+    pub fn new() -> Self {
+        Self {
+            fonts: RefCell::new(HashMap::new()),
+            default_font_alias: RefCell::new(String::new()),
+            image_cache: RefCell::new(HashMap::new()),
+            svg_cache: RefCell::new(HashMap::new())
+        }
+    }
+
+    pub fn get_font(&self, alias: &Option<String>) -> Option<TinySkiaFont> {
+        let fonts = self.fonts.borrow();
+
+        alias
+        .as_ref()
+        .and_then(|al| fonts.get(al).cloned().or_else(|| {
+            warn!("Unregistered font: {al}! Please register it first with RendererData::register_font");
+            None
+        }))
+        .or_else(|| fonts.get(self.default_font_alias.borrow().as_str()).cloned())
+    }
+
+    pub fn get_or_load_image(&self, image: &CacheableImage) -> Rc<Pixmap> {
+        self.image_cache
+        .borrow_mut()
+        .entry(*image.uuid())
+        .or_insert_with(|| Rc::new(adapter::image_to_pixmap(image)))
+        .clone()
+    }
+
+    pub fn get_or_load_svg(&self, svg: &CacheableSvg) -> Rc<usvg::Tree> {
+        self.svg_cache
+        .borrow_mut()
+        .entry(*svg.uuid())
+        .or_insert_with(|| Rc::new(adapter::svg_to_tree(svg)))
+        .clone()
+    }
+}
+
+impl RendererDataTrait for TinySkiaRendererData {
+    fn register_font(&self, bytes: &[u8], alias: &str) {
+        let data: Arc<[u8]> = Arc::from(bytes);
+
+        let file_ref = FileRef::new(&data).unwrap();
+        let font_ref = match file_ref {
+            FileRef::Font(font_ref) => font_ref,
+            FileRef::Collection(collection_ref) => collection_ref.get(0).unwrap(),
+        };
+
+        let font = TinySkiaFont {
+            // The bytes are kept alive by `data`, which is stored next to the reference
+            font_ref: unsafe { std::mem::transmute::<FontRef<'_>, FontRef<'static>>(font_ref) },
+            data: data.clone()
+        };
+
+        let mut default_alias = self.default_font_alias.borrow_mut();
+        if default_alias.is_empty() {
+            *default_alias = alias.to_string();
+        }
+
+        self.fonts.borrow_mut().insert(
+            alias.to_string(),
+            font
+        );
+    }
+
+    fn register_default_font(&self, bytes: &[u8], alias: &str) {
+        *self.default_font_alias.borrow_mut() = alias.to_string();
+        self.register_font(bytes, alias);
+    }
+
+    fn load_image(&self, image: &CacheableImage) {
+        let pixmap = adapter::image_to_pixmap(image);
+
+        self.image_cache.borrow_mut().insert(*image.uuid(), Rc::new(pixmap));
+    }
+
+    fn unload_image(&self, image: &CacheableImage) {
+        self.image_cache.borrow_mut().remove(image.uuid());
+    }
+
+    fn load_svg(&self, svg: &CacheableSvg) {
+        let tree = adapter::svg_to_tree(svg);
+
+        self.svg_cache.borrow_mut().insert(*svg.uuid(), Rc::new(tree));
+    }
+
+    fn unload_svg(&self, svg: &CacheableSvg) {
+        self.svg_cache.borrow_mut().remove(svg.uuid());
+    }
+
+    fn remove_window_data(&self, _window_id: &WindowId) {
+        // Nothing is cached per window
+    }
+
+    fn fonts(&self) -> Vec<RegisteredFont> {
+        let default_alias = self.default_font_alias.borrow();
+
+        self.fonts.borrow().iter().map(|(alias, font)| RegisteredFont {
+            alias: alias.clone(),
+            bytes: font.data.clone(),
+            default: *alias == *default_alias
+        }).collect()
+    }
+
+    fn measure_text(&self, text: &str, options: &TextOptions) -> Option<TextMetrics> {
+        let font = self.get_font(&options.font)?;
+        Some(layout::measure_text(&font.font_ref, text, options))
+    }
+
+    fn font_metrics(&self, font: &Option<String>, size: f32) -> Option<FontMetrics> {
+        let font = self.get_font(font)?;
+        Some(layout::font_metrics(&font.font_ref, size, &Location::default()))
+    }
+}
+
+
+impl Debug for TinySkiaRendererData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TinySkiaRendererData")
+        .finish_non_exhaustive()
+    }
+}
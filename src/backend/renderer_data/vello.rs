@@ -1,17 +1,22 @@
-use std::{cell::{RefCell}, collections::HashMap, fmt::Debug, mem::ManuallyDrop};
+use std::{cell::{RefCell}, collections::HashMap, fmt::Debug, mem::ManuallyDrop, sync::Arc};
 
 use log::warn;
-use skrifa::{raw::FileRef, FontRef};
-use vello::{peniko::{Blob, Font}, util::RenderContext};
+use skrifa::{instance::Location, raw::FileRef, FontRef};
+use uuid::Uuid;
+use vello::{peniko::{Blob, Font, Image}, util::RenderContext};
 
-use crate::types::{CacheableImage, CacheableSvg, WindowId};
+use crate::{renderer::{layout, vello::adapter}, types::{CacheableImage, CacheableSvg, FontMetrics, TextMetrics, TextOptions, WindowId}};
 
-use super::{RendererDataTrait};
+use super::{RegisteredFont, RendererDataTrait};
 
 pub struct VelloRendererData {
     pub context: RefCell<ManuallyDrop<RenderContext>>,
     pub fonts: RefCell<HashMap<String, VelloFont>>,
-    pub default_font_alias: RefCell<String>
+    pub default_font_alias: RefCell<String>,
+    /// Keeps the same blob for an image across frames, so Vello doesn't see it as a new image every time
+    pub image_cache: RefCell<HashMap<Uuid, Image>>,
+    /// Lazily created renderer for rendering without a surface
+    pub(crate) offscreen_renderer: RefCell<Option<vello::Renderer>>
 }
 
 #[derive(Clone)]
@@ -27,10 +32,20 @@ impl VelloRendererData {
         Self {
             context,
             fonts: RefCell::new(HashMap::new()),
-            default_font_alias: RefCell::new(String::new())
+            default_font_alias: RefCell::new(String::new()),
+            image_cache: RefCell::new(HashMap::new()),
+            offscreen_renderer: RefCell::new(None)
         }
     }
 
+    pub fn get_or_load_image(&self, image: &CacheableImage) -> Image {
+        self.image_cache
+        .borrow_mut()
+        .entry(*image.uuid())
+        .or_insert_with(|| adapter::vello_image(image))
+        .clone()
+    }
+
     pub fn get_font(&self, alias: &Option<String>) -> Option<VelloFont> {
         let fonts = self.fonts.borrow();
 
@@ -76,12 +91,12 @@ impl RendererDataTrait for VelloRendererData {
         self.register_font(bytes, alias);
     }
 
-    fn load_image(&self, _image: &CacheableImage) {
-        todo!()
+    fn load_image(&self, image: &CacheableImage) {
+        self.image_cache.borrow_mut().insert(*image.uuid(), adapter::vello_image(image));
     }
 
-    fn unload_image(&self, _image: &CacheableImage) {
-        todo!()
+    fn unload_image(&self, image: &CacheableImage) {
+        self.image_cache.borrow_mut().remove(image.uuid());
     }
 
     fn load_svg(&self, _svg: &CacheableSvg) {
@@ -95,6 +110,26 @@ impl RendererDataTrait for VelloRendererData {
     fn remove_window_data(&self, _window_id: &WindowId) {
         // Do nothing
     }
+
+    fn fonts(&self) -> Vec<RegisteredFont> {
+        let default_alias = self.default_font_alias.borrow();
+
+        self.fonts.borrow().iter().map(|(alias, font)| RegisteredFont {
+            alias: alias.clone(),
+            bytes: Arc::from(font.font.data.data()),
+            default: *alias == *default_alias
+        }).collect()
+    }
+
+    fn measure_text(&self, text: &str, options: &TextOptions) -> Option<TextMetrics> {
+        let font = self.get_font(&options.font)?;
+        Some(layout::measure_text(&font.font_ref, text, options))
+    }
+
+    fn font_metrics(&self, font: &Option<String>, size: f32) -> Option<FontMetrics> {
+        let font = self.get_font(font)?;
+        Some(layout::font_metrics(&font.font_ref, size, &Location::default()))
+    }
 }
 
 
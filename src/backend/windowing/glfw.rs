@@ -22,7 +22,9 @@ impl GlfwBackend {
 
 impl Backend for GlfwBackend {
     fn create_window(&self, det: WindowDetails) -> BackendWindows {
-        let (window, events) = self.glfw.borrow_mut().create_window(det.width, det.height, &det.title, glfw::WindowMode::Windowed).unwrap();
+        let mut glfw = self.glfw.borrow_mut();
+        glfw.window_hint(glfw::WindowHint::TransparentFramebuffer(det.transparent));
+        let (window, events) = glfw.create_window(det.width, det.height, &det.title, glfw::WindowMode::Windowed).unwrap();
         
         BackendWindows::GlfwWindow(GlfwWindow { backend: &self, window, events })
     }
@@ -0,0 +1,190 @@
+use std::{cell::{Cell, RefCell}, collections::HashMap, ffi::c_void, rc::{Rc, Weak}};
+
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use log::*;
+
+use crate::{backend::{events::{BackendEvent, Event, WindowEvent}, windowing::window::{Window, WindowDetails, WindowId}, BResult, BackendTrait}, structs::Dimensions};
+
+use super::{headless_window::{HeadlessWindow, HeadlessWindowState}, WindowBackend};
+
+
+/// A backend without any windowing system, creating virtual windows.  
+/// Events are only emitted when pushed manually with `push_event`, which makes it useful for tests.
+#[derive(Debug)]
+pub struct HeadlessBackend<T> {
+    scale: f32,
+    next_id: Cell<u64>,
+    windows: RefCell<HashMap<u64, Weak<HeadlessWindowState>>>,
+    event_receiver: Receiver<Event<T>>,
+    event_sender: Sender<Event<T>>,
+    unsubscribe: Cell<bool>
+}
+
+impl<T> HeadlessBackend<T> {
+    /// Runs the callback on the current thread, as there is no event loop that would need it.
+    pub fn create(callback: impl FnOnce(WindowBackend<T>)) -> BResult<()> {
+        callback(WindowBackend::Headless(Self::new(1.0)));
+
+        Ok(())
+    }
+
+    /// Creates a headless backend, whose windows will report the given scale factor.
+    pub fn new(scale: f32) -> Self {
+        let (event_sender, event_receiver) = unbounded();
+
+        Self {
+            scale,
+            next_id: Cell::new(0),
+            windows: RefCell::new(HashMap::new()),
+            event_receiver,
+            event_sender,
+            unsubscribe: Cell::new(false)
+        }
+    }
+
+    /// Pushes a synthetic event for the given window, as if it was emitted by a real windowing system.  
+    /// Like with other backends, positions and sizes are physical, so call `WindowEvent::scale_with` to make them logical.  
+    /// `WindowEvent::WindowSize` and `WindowEvent::ScaleFactor` also update the virtual window.
+    pub fn push_event(&self, window_id: &WindowId, event: WindowEvent) {
+        #[allow(irrefutable_let_patterns)]
+        let WindowId::Headless(id) = window_id else {
+            warn!("Tried to push an event for non-headless window {window_id:?}");
+            return;
+        };
+
+        let Some(state) = self.windows.borrow().get(id).and_then(Weak::upgrade) else {
+            warn!("Tried to push an event for closed headless window {id}");
+            return;
+        };
+
+        match &event {
+            WindowEvent::WindowSize(dimensions) => state.dimensions.set(*dimensions),
+            WindowEvent::ScaleFactor(scale) => state.target_scale.set(*scale),
+            _ => {}
+        }
+
+        self.send_event(Event::Backend(BackendEvent {
+            event,
+            window_id: window_id.clone()
+        }));
+    }
+}
+
+impl<T> BackendTrait<T> for HeadlessBackend<T> {
+    fn create_window(&self, info: WindowDetails) -> Window {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+
+        debug!("Creating headless window {id}: {info:?}");
+
+        let state = Rc::new(HeadlessWindowState {
+            id,
+            title: info.title,
+            dimensions: Cell::new(Dimensions::new(info.width, info.height)),
+            target_scale: Cell::new(self.scale),
+            mode: Cell::new(info.mode)
+        });
+
+        let mut windows = self.windows.borrow_mut();
+        windows.retain(|_, window| window.strong_count() > 0);
+        windows.insert(id, Rc::downgrade(&state));
+
+        Window::HeadlessWindow(HeadlessWindow {
+            scale: Cell::new(self.scale),
+            state
+        })
+    }
+
+    fn gl_proc_address(&self, _: &str) -> *const c_void {
+        std::ptr::null()
+    }
+
+    fn unsubscribe(&self) {
+        self.unsubscribe.set(true);
+    }
+
+    /// Calls the callback with all queued events, until unsubscribed or until no events are left,
+    /// since a headless backend has no event source of its own that could be waited on.
+    fn subscribe_events(&self, mut callback: impl FnMut(Vec<Event<T>>)) {
+        self.unsubscribe.set(false);
+
+        while !self.unsubscribe.get() {
+            let events = self.flush_events();
+            if events.is_empty() { break; }
+
+            callback(events);
+        }
+    }
+
+    fn flush_events(&self) -> Vec<Event<T>> {
+        self.event_receiver.try_iter().collect()
+    }
+
+    fn send_event(&self, event: Event<T>) {
+        self.event_sender.send(event).unwrap();
+    }
+
+    fn sender(&self) -> Sender<Event<T>> {
+        self.event_sender.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{backend::{events::{BackendEvent, Event, WindowEvent}, keys::{KeyAction, Modifiers, PhysicalKey}, windowing::window::{WindowDetails, WindowTrait}, BackendTrait}, structs::{Dimensions, Position}};
+
+    use super::HeadlessBackend;
+
+    #[test]
+    fn pushed_events_arrive_in_order() {
+        let backend = HeadlessBackend::<()>::new(2.0);
+        let window = backend.create_window(WindowDetails { width: 800, height: 600, ..Default::default() });
+        let window_id = window.id();
+
+        let pushed = [
+            WindowEvent::CursorPos(Position::new(12.0, 34.0)),
+            WindowEvent::Key(PhysicalKey::KeyA, Some("a".into()), KeyAction::Press, Modifiers::Shift),
+            WindowEvent::WindowSize(Dimensions::new(1024, 768))
+        ];
+        for event in pushed.clone() {
+            backend.push_event(&window_id, event);
+        }
+
+        let mut received = Vec::new();
+        backend.subscribe_events(|events| received.extend(events));
+
+        let expected: Vec<Event<()>> = pushed.into_iter().map(|event| Event::Backend(BackendEvent { event, window_id: window_id.clone() })).collect();
+        assert_eq!(received, expected);
+        // Resizing also resizes the virtual window
+        assert_eq!(window.physical_dimensions(), Dimensions::new(1024, 768));
+    }
+
+    #[test]
+    fn subscribe_stops_on_empty_queue() {
+        let backend = HeadlessBackend::<()>::new(1.0);
+        let mut calls = 0;
+
+        backend.subscribe_events(|_| calls += 1);
+
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn subscribe_stops_when_unsubscribed() {
+        let backend = HeadlessBackend::<()>::new(1.0);
+        let window = backend.create_window(WindowDetails::default());
+        let window_id = window.id();
+        backend.push_event(&window_id, WindowEvent::Redraw);
+
+        let mut calls = 0;
+        backend.subscribe_events(|_| {
+            calls += 1;
+            backend.push_event(&window_id, WindowEvent::CloseRequested);
+            backend.unsubscribe();
+        });
+
+        assert_eq!(calls, 1);
+        // Events pushed after unsubscribing stay queued
+        assert_eq!(backend.flush_events(), vec![Event::Backend(BackendEvent { event: WindowEvent::CloseRequested, window_id })]);
+    }
+}
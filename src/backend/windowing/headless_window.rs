@@ -0,0 +1,75 @@
+use std::{cell::Cell, rc::Rc};
+
+use raw_window_handle::HandleError;
+
+use crate::{structs::Dimensions, traits::RendererDataTrait, types::RendererData};
+
+use crate::backend::windowing::window::{WindowHandles, WindowId, WindowModes, WindowTrait};
+
+
+/// A virtual window without a surface, created by the `HeadlessBackend`.  
+/// As there are no window handles, only offscreen rendering is possible.
+#[derive(Debug)]
+pub struct HeadlessWindow {
+    pub(crate) state: Rc<HeadlessWindowState>,
+    pub scale: Cell<f32>
+}
+
+/// The parts of a headless window that can be changed by events pushed to the backend
+#[derive(Debug)]
+pub(crate) struct HeadlessWindowState {
+    pub id: u64,
+    pub title: String,
+    pub dimensions: Cell<Dimensions<u32>>,
+    pub target_scale: Cell<f32>,
+    pub mode: Cell<WindowModes>
+}
+
+impl HeadlessWindow {
+    pub fn title(&self) -> &str {
+        &self.state.title
+    }
+
+    pub fn mode(&self) -> WindowModes {
+        self.state.mode.get()
+    }
+}
+
+impl WindowTrait for HeadlessWindow {
+    fn handles(&self) -> Result<WindowHandles<'_>, HandleError> {
+        Err(HandleError::Unavailable)
+    }
+
+    fn physical_dimensions(&self) -> Dimensions<u32> {
+        self.state.dimensions.get()
+    }
+
+    fn dimensions(&self) -> Dimensions<u32> {
+        self.physical_dimensions() / self.current_scale()
+    }
+
+    fn set_mode(&self, window_mode: WindowModes) {
+        self.state.mode.set(window_mode);
+    }
+
+    fn target_scale(&self) -> f32 {
+        self.state.target_scale.get()
+    }
+
+    fn current_scale(&self) -> f32 {
+        self.scale.get()
+    }
+
+    fn set_scale(&self, scale: f32) {
+        self.scale.set(scale);
+    }
+
+    fn id(&self) -> WindowId {
+        WindowId::Headless(self.state.id)
+    }
+
+    fn close(self, renderer_data: &RendererData) {
+        renderer_data.remove_window_data(&self.id());
+        // self is dropped, which also removes the window from the backend.
+    }
+}
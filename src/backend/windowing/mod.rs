@@ -6,6 +6,10 @@ pub mod winit;
 pub mod winit_window;
 #[cfg(feature = "b-glfw")]
 pub mod glfw;
+#[cfg(feature = "b-headless")]
+pub mod headless;
+#[cfg(feature = "b-headless")]
+pub mod headless_window;
 
 use enum_dispatch::enum_dispatch;
 use log::*;
@@ -15,6 +19,8 @@ use strum::IntoEnumIterator;
 use self::glfw::*;
 #[cfg(feature = "b-winit")]
 use self::winit::*;
+#[cfg(feature = "b-headless")]
+use self::headless::*;
 
 use super::{errors::{BackendError, BackendInitError}, BResult, BackendType};
 
@@ -24,13 +30,19 @@ pub enum WindowBackend<T> {
     #[cfg(feature = "b-winit")]
     Winit(WinitBackend<T>),
     #[cfg(feature = "b-glfw")]
-    Glfw(GlfwBackend<T>)
+    Glfw(GlfwBackend<T>),
+    #[cfg(feature = "b-headless")]
+    Headless(HeadlessBackend<T>)
 }
 
 impl<T> WindowBackend<T> {
     pub fn create(callback: impl FnOnce(WindowBackend<T>) + Copy + Send + 'static) -> BResult<()> {
         let backends = BackendType::iter();
         for typ in backends {
+            // Headless backends have to be requested explicitly, as they never show any windows
+            #[cfg(feature = "b-headless")]
+            if matches!(typ, BackendType::Headless) { continue }
+
             match Self::create_type(&typ, callback) {
                 Ok(()) => return Ok(()),
                 Err(err) => warn!("Error initalizing {typ:?} backend: {err}; attempting next backend..."),
@@ -48,6 +60,10 @@ impl<T> WindowBackend<T> {
             #[cfg(feature = "b-winit")]
             BackendType::Winit => {
                 WinitBackend::create(callback)?;
+            },
+            #[cfg(feature = "b-headless")]
+            BackendType::Headless => {
+                HeadlessBackend::create(callback)?;
             }
         }
         Ok(())
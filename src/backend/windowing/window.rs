@@ -10,13 +10,16 @@ pub struct WindowDetails {
     pub width: u32,
     pub height: u32,
     pub title: String,
-    pub mode: WindowModes
+    pub mode: WindowModes,
+    /// Lets whatever is behind the window show through where the renderer's clear color
+    /// (and what is drawn over it) isn't opaque. Not every platform and renderer supports this.
+    pub transparent: bool
 }
 
 impl Default for WindowDetails {
     fn default() -> Self {
         Self {
-            width: 800, height: 600, title: String::from("Lumi2D Window"), mode: WindowModes::Maximized
+            width: 800, height: 600, title: String::from("Lumi2D Window"), mode: WindowModes::Maximized, transparent: false
         }
     }
 }
@@ -36,6 +39,8 @@ pub enum Window {
     GlfwWindow(super::glfw::GlfwWindow),
     #[cfg(feature = "b-winit")]
     WinitWindow(super::winit_window::WinitWindow),
+    #[cfg(feature = "b-headless")]
+    HeadlessWindow(super::headless_window::HeadlessWindow),
 }
 
 impl Window {
@@ -62,7 +67,9 @@ pub trait WindowTrait {
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum WindowId {
     #[cfg(feature = "b-winit")]
-    Winit(winit::window::WindowId)
+    Winit(winit::window::WindowId),
+    #[cfg(feature = "b-headless")]
+    Headless(u64)
 }
 
 
@@ -189,7 +189,7 @@ impl ApplicationHandler<WinitMessage> for WinitApp {
                 let mut attributes = WindowAttributes::default()
                 .with_inner_size(PhysicalSize::new(details.width, details.height))
                 .with_title(details.title)
-                .with_transparent(true);
+                .with_transparent(details.transparent);
 
                 match details.mode {
                     WindowModes::Fullscreen => attributes.fullscreen = if let Some(handle) = monitor.video_modes().next() {
@@ -0,0 +1,154 @@
+use std::{sync::OnceLock, time::{Duration, Instant}};
+
+use lumi2d::prelude::*;
+use strum::IntoEnumIterator;
+
+
+const USAGE: &str = "\
+Usage: lumi2d-replay <capture> [options]
+
+Options:
+    --renderer <name>   Renderer to replay on, like vello, skia or tinyskia (default: the first available one)
+    --window <index>    Window to replay, in the order the windows were first captured (default: 0)
+    --frame <index>     Only shows the given frame, instead of playing all of them";
+
+/// The longest the replay sleeps between frames before handling window events again
+const EVENT_POLL: Duration = Duration::from_millis(10);
+
+#[derive(Debug)]
+struct Options {
+    path: String,
+    renderer: Option<RendererType>,
+    window: u32,
+    frame: Option<usize>
+}
+
+static OPTIONS: OnceLock<Options> = OnceLock::new();
+
+fn parse_options() -> Result<Options, String> {
+    let mut args = std::env::args().skip(1);
+    let mut options = Options { path: String::new(), renderer: None, window: 0, frame: None };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("Missing value for {name}"));
+
+        match arg.as_str() {
+            "--renderer" => {
+                let name = value("--renderer")?.to_lowercase().replace(['-', '_'], "");
+                let renderer = RendererType::iter()
+                .find(|typ| format!("{typ:?}").to_lowercase() == name)
+                .ok_or_else(|| format!("Unknown or disabled renderer: {name}"))?;
+                options.renderer = Some(renderer);
+            },
+            "--window" => options.window = value("--window")?.parse().map_err(|err| format!("Invalid window: {err}"))?,
+            "--frame" => options.frame = Some(value("--frame")?.parse().map_err(|err| format!("Invalid frame: {err}"))?),
+            "--help" | "-h" => return Err(String::new()),
+            path if options.path.is_empty() && !path.starts_with("--") => options.path = path.to_string(),
+            other => return Err(format!("Unexpected argument: {other}"))
+        }
+    }
+
+    if options.path.is_empty() { return Err("Missing capture file".to_string()) }
+    Ok(options)
+}
+
+fn main() {
+    match parse_options() {
+        Ok(options) => OPTIONS.set(options).unwrap(),
+        Err(message) => {
+            eprintln!("{message}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    }
+
+    Backend::create(|backend| {
+        let options = OPTIONS.get().unwrap();
+
+        let mut replay = match Replay::open(&options.path) {
+            Ok(replay) => replay,
+            Err(err) => {
+                eprintln!("Failed to read {}: {err}", options.path);
+                std::process::exit(1);
+            }
+        };
+
+        let frame_count = replay.window_frames(options.window).count();
+        let Some(first) = replay.window_frames(options.window).next() else {
+            eprintln!("The capture has no frames for window {}", options.window);
+            std::process::exit(1);
+        };
+        if options.frame.is_some_and(|frame| frame >= frame_count) {
+            eprintln!("The capture only has {frame_count} frames for window {}", options.window);
+            std::process::exit(1);
+        }
+
+        let window = backend.create_window(WindowDetails {
+            width: first.dimensions.width,
+            height: first.dimensions.height,
+            title: format!("Replay of {}", options.path),
+            mode: WindowModes::Windowed,
+            ..Default::default()
+        });
+
+        let renderer = match options.renderer {
+            Some(typ) => {
+                let renderer = Renderer::create_type(&typ, &window, &backend.renderer_data()).unwrap();
+                backend.transform_renderer_data(&renderer);
+                renderer
+            },
+            None => window.create_renderer(&backend).unwrap()
+        };
+
+        replay.prepare(&backend.data());
+        let frames: Vec<&CapturedFrame> = replay.window_frames(options.window).collect();
+
+        let start = Instant::now();
+        let first_time = frames[0].time;
+        let length = frames[frames.len() - 1].time - first_time;
+        let mut shown = None;
+
+        loop {
+            for event in backend.flush_events() {
+                if let Event::Backend(backend_event) = event {
+                    match backend_event.event {
+                        WindowEvent::CloseRequested => {
+                            backend.unsubscribe();
+                            return;
+                        },
+                        WindowEvent::WindowSize(_) => {
+                            renderer.recreate(&window, &backend.renderer_data());
+                            shown = None;
+                        },
+                        _ => {}
+                    }
+                }
+            }
+
+            // Plays the frames at their captured pace, starting over at the end
+            let (index, wait) = match options.frame {
+                Some(index) => (index, EVENT_POLL),
+                None if length.is_zero() => (frames.len() - 1, EVENT_POLL),
+                None => {
+                    let elapsed = Duration::from_nanos((start.elapsed().as_nanos() % length.as_nanos()) as u64);
+                    let next = frames.partition_point(|frame| frame.time - first_time <= elapsed);
+                    // The last frame stays until the capture starts over
+                    let next_time = frames.get(next).map_or(length, |frame| frame.time - first_time);
+                    (next - 1, next_time.saturating_sub(elapsed))
+                }
+            };
+
+            // A frame is only drawn once, when it was captured
+            if shown != Some(index) {
+                let frame = frames[index];
+                if window.current_scale() != frame.scale {
+                    window.set_scale(frame.scale);
+                }
+                renderer.set_clear_color(frame.clear_color);
+                renderer.render(&window, &backend.data(), frame.objects.iter().collect()).unwrap();
+                shown = Some(index);
+            }
+
+            std::thread::sleep(wait.min(EVENT_POLL));
+        }
+    }).unwrap();
+}
@@ -7,9 +7,11 @@ pub mod structs;
 use std::sync::OnceLock;
 
 pub mod types {
-    pub use crate::renderer::{Renderer, RendererType, RResult, objects::*, images::*, svgs::*, text::*, errors::*};
-    pub use crate::backend::{Backend, BackendType, BResult, events::*, keys::*, windowing::{*, window::*}, renderer_data::RendererData};
+    pub use crate::renderer::{Renderer, RendererType, RResult, objects::*, brushes::*, hit_test::*, paths::*, scene::*, strokes::*, images::*, svgs::*, text::*, transforms::*, errors::*};
+    pub use crate::backend::{Backend, BackendType, BResult, events::*, keys::*, windowing::{*, window::*}, renderer_data::{RendererData, RegisteredFont}};
     pub use crate::structs::*;
+    #[cfg(feature = "serde")]
+    pub use crate::renderer::capture::*;
 }
 
 pub mod traits {
@@ -24,6 +26,7 @@ pub mod prelude {
 
 static VSYNC: OnceLock<bool> = OnceLock::new();
 static POLLING: OnceLock<bool> = OnceLock::new();
+static DEBUG_OVERLAY: OnceLock<bool> = OnceLock::new();
 
 pub fn vsync() -> bool {
     *VSYNC.get_or_init(|| {
@@ -39,4 +42,13 @@ pub fn polling() -> bool {
 
         env.is_ok_and(|val| matches!(val.as_str(), "true" | "yes" | "on" | "1"))
     })
-}
\ No newline at end of file
+}
+
+/// Whether renderers start with their debug overlay enabled, see `RendererTrait::set_debug_overlay`
+pub fn debug_overlay() -> bool {
+    *DEBUG_OVERLAY.get_or_init(|| {
+        let env = std::env::var("LUMI_DEBUG_OVERLAY");
+
+        env.is_ok_and(|val| matches!(val.as_str(), "true" | "yes" | "on" | "1"))
+    })
+}
@@ -0,0 +1,69 @@
+/// Blurs interleaved 8 bit pixels in place, approximating a gaussian blur with three box blurs.
+/// Colors have to be premultiplied, so transparent pixels don't bleed into their neighbours.
+pub(crate) fn gaussian_blur(pixels: &mut [u8], width: usize, height: usize, channels: usize, sigma: f32) {
+    if sigma <= 0.0 || width == 0 || height == 0 { return }
+
+    let mut buffer = vec![0; pixels.len()];
+
+    for radius in box_radii(sigma) {
+        box_blur_horizontal(pixels, &mut buffer, width, height, channels, radius);
+        box_blur_vertical(&buffer, pixels, width, height, channels, radius);
+    }
+}
+
+/// The radii of three box blurs that together come close to a gaussian blur,
+/// see "Fast Almost-Gaussian Filtering" by Peter Kovesi.
+fn box_radii(sigma: f32) -> [usize; 3] {
+    let passes = 3.0;
+    let ideal_width = (12.0 * sigma * sigma / passes + 1.0).sqrt();
+
+    let mut lower = (ideal_width.floor() as i32).max(1);
+    if lower % 2 == 0 { lower -= 1; }
+    let upper = lower + 2;
+
+    let lower_passes = ((12.0 * sigma * sigma - passes * (lower * lower) as f32 - 4.0 * passes * lower as f32 - 3.0 * passes)
+    / (-4.0 * lower as f32 - 4.0)).round() as i32;
+
+    std::array::from_fn(|pass| {
+        let width = if (pass as i32) < lower_passes { lower } else { upper };
+        (width as usize - 1) / 2
+    })
+}
+
+fn box_blur_horizontal(source: &[u8], target: &mut [u8], width: usize, height: usize, channels: usize, radius: usize) {
+    let window = (radius * 2 + 1) as u32;
+    let last = width as isize - 1;
+
+    for y in 0..height {
+        let row = y * width * channels;
+
+        for channel in 0..channels {
+            let at = |x: isize| source[row + x.clamp(0, last) as usize * channels + channel] as u32;
+            let mut sum: u32 = (-(radius as isize)..=radius as isize).map(at).sum();
+
+            for x in 0..width as isize {
+                target[row + x as usize * channels + channel] = ((sum + window / 2) / window) as u8;
+                sum = sum + at(x + radius as isize + 1) - at(x - radius as isize);
+            }
+        }
+    }
+}
+
+fn box_blur_vertical(source: &[u8], target: &mut [u8], width: usize, height: usize, channels: usize, radius: usize) {
+    let window = (radius * 2 + 1) as u32;
+    let last = height as isize - 1;
+    let stride = width * channels;
+
+    for x in 0..width {
+        for channel in 0..channels {
+            let column = x * channels + channel;
+            let at = |y: isize| source[y.clamp(0, last) as usize * stride + column] as u32;
+            let mut sum: u32 = (-(radius as isize)..=radius as isize).map(at).sum();
+
+            for y in 0..height as isize {
+                target[y as usize * stride + column] = ((sum + window / 2) / window) as u8;
+                sum = sum + at(y + radius as isize + 1) - at(y - radius as isize);
+            }
+        }
+    }
+}
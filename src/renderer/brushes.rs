@@ -0,0 +1,165 @@
+use std::ops::Mul;
+
+use super::images::CacheableImage;
+
+
+/// Describes how a shape, text or SVG is filled.
+/// Gradient and pattern coordinates use the same coordinate space as the object they are applied to.
+/// For paragraphs, they are relative to the paragraph's position.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Brush {
+    /// A RGBA color, like `0xFF0000FF` for opaque red
+    Solid(u32),
+    Gradient(Gradient),
+    /// An image, repeated in both directions
+    Image(ImagePattern)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Gradient {
+    pub kind: GradientKind,
+    pub stops: Vec<GradientStop>,
+    pub extend: Extend
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GradientKind {
+    Linear { start: (f32, f32), end: (f32, f32) },
+    Radial { center: (f32, f32), radius: f32 },
+    /// Angles are in radians, starting at the positive x axis and going clockwise
+    Sweep { center: (f32, f32), start_angle: f32, end_angle: f32 }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GradientStop {
+    /// Position along the gradient, from 0.0 to 1.0
+    pub offset: f32,
+    pub color: u32
+}
+
+/// Decides how a gradient continues outside of its start and end
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Extend {
+    #[default]
+    Pad,
+    Repeat,
+    Reflect
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImagePattern {
+    pub image: CacheableImage,
+    /// Offset of the top left corner of the first tile
+    pub x: f32,
+    pub y: f32,
+    pub scale: f32
+}
+
+impl Brush {
+    #[inline]
+    pub fn linear_gradient(start: (f32, f32), end: (f32, f32), stops: &[(f32, u32)]) -> Self {
+        Self::gradient(GradientKind::Linear { start, end }, stops)
+    }
+
+    #[inline]
+    pub fn radial_gradient(center: (f32, f32), radius: f32, stops: &[(f32, u32)]) -> Self {
+        Self::gradient(GradientKind::Radial { center, radius }, stops)
+    }
+
+    #[inline]
+    pub fn sweep_gradient(center: (f32, f32), start_angle: f32, end_angle: f32, stops: &[(f32, u32)]) -> Self {
+        Self::gradient(GradientKind::Sweep { center, start_angle, end_angle }, stops)
+    }
+
+    /// Shorthand for a gradient with `Extend::Pad`, taking the stops as (offset, color) pairs
+    pub fn gradient(kind: GradientKind, stops: &[(f32, u32)]) -> Self {
+        Self::Gradient(Gradient {
+            kind,
+            stops: stops.iter().map(|&(offset, color)| GradientStop { offset, color }).collect(),
+            extend: Extend::default()
+        })
+    }
+
+    /// An image pattern, tiled from the origin at its original size
+    #[inline]
+    pub fn image(image: CacheableImage) -> Self {
+        Self::Image(ImagePattern { image, x: 0.0, y: 0.0, scale: 1.0 })
+    }
+
+    /// Sets the extend mode, if this is a gradient
+    pub fn with_extend(self, extend: Extend) -> Self {
+        match self {
+            Self::Gradient(gradient) => Self::Gradient(Gradient { extend, ..gradient }),
+            other => other
+        }
+    }
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self::Solid(0xFFFFFFFF)
+    }
+}
+
+impl From<u32> for Brush {
+    #[inline]
+    fn from(color: u32) -> Self {
+        Self::Solid(color)
+    }
+}
+
+impl From<Gradient> for Brush {
+    #[inline]
+    fn from(gradient: Gradient) -> Self {
+        Self::Gradient(gradient)
+    }
+}
+
+impl From<ImagePattern> for Brush {
+    #[inline]
+    fn from(pattern: ImagePattern) -> Self {
+        Self::Image(pattern)
+    }
+}
+
+impl Mul<f32> for Brush {
+    type Output = Self;
+
+    fn mul(self, with: f32) -> Self::Output {
+        if with == 1.0 { return self; }
+
+        match self {
+            Brush::Solid(color) => Brush::Solid(color),
+            Brush::Gradient(gradient) => Brush::Gradient(Gradient {
+                kind: match gradient.kind {
+                    GradientKind::Linear { start, end } => GradientKind::Linear {
+                        start: (start.0 * with, start.1 * with),
+                        end: (end.0 * with, end.1 * with)
+                    },
+                    GradientKind::Radial { center, radius } => GradientKind::Radial {
+                        center: (center.0 * with, center.1 * with),
+                        radius: radius * with
+                    },
+                    GradientKind::Sweep { center, start_angle, end_angle } => GradientKind::Sweep {
+                        center: (center.0 * with, center.1 * with),
+                        start_angle,
+                        end_angle
+                    }
+                },
+                ..gradient
+            }),
+            Brush::Image(pattern) => Brush::Image(ImagePattern {
+                x: pattern.x * with,
+                y: pattern.y * with,
+                scale: pattern.scale * with,
+                image: pattern.image
+            })
+        }
+    }
+}
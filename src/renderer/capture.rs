@@ -0,0 +1,327 @@
+use std::{any::Any, cell::RefCell, collections::{HashMap, HashSet}, fs::File, io::{BufReader, BufWriter, ErrorKind, Read, Write}, path::Path, time::{Duration, Instant}};
+
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{backend::{renderer_data::{RegisteredFont, RendererData, RendererDataTrait}, windowing::window::{Window, WindowId, WindowTrait}}, structs::Dimensions};
+
+use super::{objects::Object, Renderer, RendererTrait};
+
+
+/// Written at the start of every capture, followed by the format version
+const MAGIC: &[u8; 8] = b"LUMI2DCP";
+const VERSION: u32 = 3;
+
+thread_local! {
+    /// The assets of the capture that is currently written or read on this thread
+    static ASSETS: RefCell<Option<Assets>> = const { RefCell::new(None) };
+}
+
+/// Images and SVGs are only stored once per capture, later frames refer to them by their Uuid
+#[derive(Default)]
+struct Assets {
+    written: HashSet<Uuid>,
+    read: HashMap<Uuid, Box<dyn Any>>
+}
+
+#[derive(Serialize, Deserialize)]
+enum Asset<T> {
+    Data(Uuid, T),
+    Reference(Uuid)
+}
+
+pub(crate) fn serialize_asset<S: Serializer, T: Serialize>(uuid: Uuid, data: T, serializer: S) -> Result<S::Ok, S::Error> {
+    let written = ASSETS.with_borrow_mut(|assets| assets.as_mut().is_some_and(|assets| !assets.written.insert(uuid)));
+
+    if written {
+        Asset::<T>::Reference(uuid).serialize(serializer)
+    } else {
+        Asset::Data(uuid, data).serialize(serializer)
+    }
+}
+
+pub(crate) fn deserialize_asset<'de, D: Deserializer<'de>, T: Deserialize<'de>, A: Clone + 'static>(deserializer: D, build: impl FnOnce(Uuid, T) -> A) -> Result<A, D::Error> {
+    match Asset::<T>::deserialize(deserializer)? {
+        Asset::Data(uuid, data) => {
+            let asset = build(uuid, data);
+            ASSETS.with_borrow_mut(|assets| if let Some(assets) = assets {
+                assets.read.insert(uuid, Box::new(asset.clone()));
+            });
+            Ok(asset)
+        },
+        Asset::Reference(uuid) => ASSETS.with_borrow(|assets| {
+            assets.as_ref()
+            .and_then(|assets| assets.read.get(&uuid))
+            .and_then(|asset| asset.downcast_ref::<A>())
+            .cloned()
+        })
+        .ok_or_else(|| D::Error::custom(format!("Missing image or SVG {uuid} in capture")))
+    }
+}
+
+/// Makes the assets available to `serialize_asset` and `deserialize_asset` while running `function`
+fn with_assets<R>(assets: &mut Assets, function: impl FnOnce() -> R) -> R {
+    ASSETS.set(Some(std::mem::take(assets)));
+    let result = function();
+    *assets = ASSETS.take().unwrap_or_default();
+    result
+}
+
+
+#[derive(Debug, Error)]
+pub enum CaptureError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Encoding(#[from] bincode::Error),
+    #[error("Not a Lumi2D capture, or one made by an incompatible version!")]
+    Format
+}
+
+/// A frame as it was passed to `RendererTrait::render`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedFrame {
+    /// Time since the capture was started
+    pub time: Duration,
+    /// Index of the window, in the order the windows were first captured
+    pub window: u32,
+    pub dimensions: Dimensions<u32>,
+    pub scale: f32,
+    pub clear_color: u32,
+    pub objects: Vec<Object>
+}
+
+#[derive(Deserialize)]
+enum Record {
+    Font(RegisteredFont),
+    Frame(CapturedFrame)
+}
+
+/// Same encoding as `Record`, without having to clone the objects
+#[derive(Serialize)]
+enum RecordRef<'a> {
+    Font(&'a RegisteredFont),
+    Frame {
+        time: Duration,
+        window: u32,
+        dimensions: Dimensions<u32>,
+        scale: f32,
+        clear_color: u32,
+        objects: &'a [&'a Object]
+    }
+}
+
+/// ### Records frames into a capture, which can be replayed on any renderer.
+/// Images, SVGs and fonts are written the first time they are used, so the capture is self-contained.
+/// Replay captures with `Replay`, or with the `lumi2d-replay` binary.
+pub struct Capture<W: Write = BufWriter<File>> {
+    writer: W,
+    start: Instant,
+    windows: Vec<WindowId>,
+    fonts: HashSet<String>,
+    assets: Assets
+}
+
+impl Capture {
+    /// Creates the capture file, replacing it if it exists
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, CaptureError> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> Capture<W> {
+    pub fn new(mut writer: W) -> Result<Self, CaptureError> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+
+        Ok(Self {
+            writer,
+            start: Instant::now(),
+            windows: Vec::new(),
+            fonts: HashSet::new(),
+            assets: Assets::default()
+        })
+    }
+
+    /// Records a frame. Call it with the same arguments as `RendererTrait::render`.
+    pub fn record(&mut self, window: &Window, renderer: &Renderer, data: &RendererData, objects: &[&Object]) -> Result<(), CaptureError> {
+        self.record_frame(&window.id(), window.physical_dimensions(), window.current_scale(), renderer.clear_color(), data, objects)
+    }
+
+    fn record_frame(&mut self, id: &WindowId, dimensions: Dimensions<u32>, scale: f32, clear_color: u32, data: &RendererData, objects: &[&Object]) -> Result<(), CaptureError> {
+        for font in data.fonts() {
+            if self.fonts.insert(font.alias.clone()) {
+                bincode::serialize_into(&mut self.writer, &RecordRef::Font(&font))?;
+            }
+        }
+
+        let index = self.windows.iter().position(|window| window == id).unwrap_or_else(|| {
+            self.windows.push(id.clone());
+            self.windows.len() - 1
+        });
+
+        let record = RecordRef::Frame {
+            time: self.start.elapsed(),
+            window: index as u32,
+            dimensions,
+            scale,
+            clear_color,
+            objects
+        };
+
+        let writer = &mut self.writer;
+        with_assets(&mut self.assets, || bincode::serialize_into(writer, &record))?;
+        Ok(())
+    }
+
+    /// Writes everything that is still buffered, so the capture can be read while it is recorded
+    pub fn flush(&mut self) -> Result<(), CaptureError> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+impl<W: Write> Drop for Capture<W> {
+    fn drop(&mut self) {
+        self.writer.flush().ok();
+    }
+}
+
+
+/// ### A capture, read back into memory.
+/// Register its fonts and resolve its paragraphs with `Replay::prepare`, then render the objects of `Replay::frame`.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub fonts: Vec<RegisteredFont>,
+    pub frames: Vec<CapturedFrame>
+}
+
+impl Replay {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CaptureError> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    /// Reads every frame of the capture. A capture that was cut off, for example because the app crashed, ends at the last complete frame.
+    pub fn read(mut reader: impl Read) -> Result<Self, CaptureError> {
+        let mut header = [0; 12];
+        reader.read_exact(&mut header).map_err(|_| CaptureError::Format)?;
+        if header[..8] != MAGIC[..] || header[8..] != VERSION.to_le_bytes() { return Err(CaptureError::Format) }
+
+        let mut replay = Self { fonts: Vec::new(), frames: Vec::new() };
+        let mut assets = Assets::default();
+
+        loop {
+            match with_assets(&mut assets, || bincode::deserialize_from(&mut reader)) {
+                Ok(Record::Font(font)) => replay.fonts.push(font),
+                Ok(Record::Frame(frame)) => replay.frames.push(frame),
+                Err(err) if matches!(&*err, bincode::ErrorKind::Io(io) if io.kind() == ErrorKind::UnexpectedEof) => break,
+                Err(err) => return Err(err.into())
+            }
+        }
+
+        Ok(replay)
+    }
+
+    /// Registers the captured fonts and lays out the paragraphs again, for the renderer the data belongs to
+    pub fn prepare(&mut self, data: &RendererData) {
+        for font in &self.fonts {
+            if font.default {
+                data.register_default_font(&font.bytes, &font.alias);
+            } else {
+                data.register_font(&font.bytes, &font.alias);
+            }
+        }
+
+        for frame in &mut self.frames {
+            frame.objects = frame.objects.iter().map(|object| resolve_paragraphs(object, data)).collect();
+        }
+    }
+
+    /// The frames of one window, in the order they were captured
+    pub fn window_frames(&self, window: u32) -> impl Iterator<Item = &CapturedFrame> {
+        self.frames.iter().filter(move |frame| frame.window == window)
+    }
+}
+
+fn resolve_paragraphs(object: &Object, data: &RendererData) -> Object {
+    let resolve_all = |children: &[Object]| children.iter().map(|child| resolve_paragraphs(child, data)).collect();
+
+    match object {
+        Object::Paragraph { paragraph, position } => Object::Paragraph { paragraph: paragraph.resolve(data), position: position.clone() },
+        Object::Group { transform, children } => Object::Group { transform: *transform, children: resolve_all(children) },
+        Object::Clip { shape, children } => Object::Clip { shape: shape.clone(), children: resolve_all(children) },
+        Object::Layer { opacity, blend, children } => Object::Layer { opacity: *opacity, blend: *blend, children: resolve_all(children) },
+        Object::Tagged { id, object } => Object::Tagged { id: *id, object: Box::new(resolve_paragraphs(object, data)) },
+        other => other.clone()
+    }
+}
+
+#[cfg(all(test, feature = "r-tiny-skia", feature = "b-headless"))]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{backend::{renderer_data::{RendererData, RendererDataTrait}, windowing::window::WindowId}, renderer::{images::{CacheableImage, PixelFormat}, objects::Object, text::TextOptions, RendererType}, structs::Dimensions};
+
+    use super::{Capture, Replay};
+
+    const FONT: &[u8] = include_bytes!("../../examples/JetBrains_Mono.ttf");
+    const DIMENSIONS: Dimensions<u32> = Dimensions { width: 64, height: 48 };
+
+    #[test]
+    fn replayed_frames_render_like_the_captured_ones() {
+        let data = RendererData::create(RendererType::TinySkia).unwrap();
+        data.register_default_font(FONT, "mono");
+
+        let image = CacheableImage::new(vec![0x80; 4 * 4 * 4].into(), PixelFormat::RGBA8, Dimensions::new(4, 4));
+        let paragraph = data.create_paragraph("Replay".to_string(), 30, None, TextOptions { size: 8.0, ..Default::default() });
+        let frames = [
+            vec![Object::rectangle(2.0, 2.0, 10.0, 10.0, 0xFF00FFFF, None), Object::image(12.0, 2.0, 8.0, 8.0, image.clone())],
+            vec![Object::image(0.0, 0.0, 16.0, 16.0, image), Object::paragraph(2.0, 10.0, paragraph)]
+        ];
+
+        let mut bytes = Vec::new();
+        let mut capture = Capture::new(&mut bytes).unwrap();
+        for objects in &frames {
+            let objects: Vec<&Object> = objects.iter().collect();
+            capture.record_frame(&WindowId::Headless(7), DIMENSIONS, 2.0, 0x202020FF, &data, &objects).unwrap();
+        }
+        drop(capture);
+
+        let replay_data = RendererData::create(RendererType::TinySkia).unwrap();
+        let mut replay = Replay::read(bytes.as_slice()).unwrap();
+        replay.prepare(&replay_data);
+
+        assert_eq!(replay.fonts.len(), 1);
+        assert_eq!(replay.window_frames(0).count(), frames.len());
+        for (frame, objects) in replay.window_frames(0).zip(&frames) {
+            assert_eq!((frame.dimensions, frame.scale, frame.clear_color), (DIMENSIONS, 2.0, 0x202020FF));
+
+            let captured = data.render_offscreen(objects.iter().collect(), DIMENSIONS, 2.0).unwrap();
+            let replayed = replay_data.render_offscreen(frame.objects.iter().collect(), DIMENSIONS, 2.0).unwrap();
+            assert_eq!(captured.pixels(), replayed.pixels());
+        }
+        // The image is only stored once, so both frames share its pixels
+        let (Object::Image { image: first, .. }, Object::Image { image: second, .. }) = (&replay.frames[0].objects[1], &replay.frames[1].objects[0]) else {
+            panic!("The images weren't replayed as images")
+        };
+        assert!(Arc::ptr_eq(&first.pixels(), &second.pixels()));
+    }
+
+    #[test]
+    fn truncated_captures_end_at_the_last_frame() {
+        let data = RendererData::create(RendererType::TinySkia).unwrap();
+        let objects = [Object::rectangle(0.0, 0.0, 10.0, 10.0, 0xFFFFFFFF, None)];
+
+        let mut bytes = Vec::new();
+        let mut capture = Capture::new(&mut bytes).unwrap();
+        for _ in 0..2 {
+            capture.record_frame(&WindowId::Headless(0), DIMENSIONS, 1.0, 0, &data, &objects.iter().collect::<Vec<_>>()).unwrap();
+        }
+        drop(capture);
+        bytes.truncate(bytes.len() - 3);
+
+        let replay = Replay::read(bytes.as_slice()).unwrap();
+        assert_eq!(replay.frames.len(), 1);
+        assert_eq!(replay.frames[0].objects, objects);
+    }
+}
@@ -8,11 +8,21 @@ pub enum RendererError {
     #[cfg(feature = "r-wgpu")]
     #[error(transparent)]
     Wgpu(super::wgpu::errors::WgpuRendererError),
+    #[cfg(feature = "r-vello")]
+    #[error(transparent)]
+    Vello(super::vello::errors::VelloRendererError),
+    #[cfg(feature = "r-tiny-skia")]
+    #[error(transparent)]
+    TinySkia(super::tiny_skia::errors::TinySkiaRendererError),
     
     #[error("No renderer could be created! Unable to continue!")]
     NoRenderer,
     #[error("Could not get required window handles!")]
-    WindowHandles
+    WindowHandles,
+    #[error("The RendererData has not been initialized by a renderer yet!")]
+    Uninitialized,
+    #[error("Cannot render to an image with a width or height of zero!")]
+    ZeroSize
 }
 
 #[cfg(feature = "r-skia")]
@@ -0,0 +1,280 @@
+use super::{objects::{ClipShape, MeasureSvg, MeasureText, Object, Rect, Rounding}, paths::{FillRule, Path, PathElement}, text::ParagraphTrait};
+
+
+/// Line segments per curve when flattening paths
+const CURVE_SEGMENTS: usize = 16;
+
+/// The object under a point, found with `RendererData::hit_test`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hit {
+    /// Index of the top level object that was hit
+    pub index: usize,
+    /// Id of the innermost `Object::Tagged` that was hit, if there is one
+    pub id: Option<u64>
+}
+
+/// Finds the topmost object containing the logical point, which is the last one drawn.
+/// `measure_text` measures `Object::Text` as (width, height) from its text, font and size.
+/// `measure_svg` measures the SVG of `Object::Svg`, which is hit in its whole rect if it can't be measured.
+pub(crate) fn hit_test(objects: &[&Object], x: f32, y: f32, measure_text: MeasureText, measure_svg: MeasureSvg) -> Option<Hit> {
+    objects.iter().enumerate().rev().find_map(|(index, object)| {
+        hit_object(object, x, y, measure_text, measure_svg).map(|id| Hit { index, id })
+    })
+}
+
+/// `None` if the object wasn't hit, otherwise the id of the innermost tagged object that was
+fn hit_object(object: &Object, x: f32, y: f32, measure_text: MeasureText, measure_svg: MeasureSvg) -> Option<Option<u64>> {
+    let hit = match object {
+        Object::Rectangle { rounding, rect, stroke, .. } => {
+            let stroke = stroke.as_ref().map_or(0.0, |stroke| stroke.width / 2.0);
+            let rounding = rounding.map(|r| Rounding::new(
+                grow_radius(r.top_l, stroke), grow_radius(r.top_r, stroke), grow_radius(r.bottom_l, stroke), grow_radius(r.bottom_r, stroke)
+            ));
+
+            rounded_rect_contains(&rect.outset(stroke), rounding.as_ref(), x, y)
+        },
+        Object::Text { text, font, size, position, .. } => measure_text(text, font, *size)
+        .is_some_and(|(width, height)| rect_contains(&Rect { x: position.x, y: position.y, width, height }, x, y)),
+        Object::Paragraph { paragraph, position } => {
+            rect_contains(&Rect { x: position.x, y: position.y, width: paragraph.width(), height: paragraph.height() }, x, y)
+        },
+        Object::Image { image, rect, source, fit } => {
+            let size = source.map_or_else(|| (image.dimensions().width as f32, image.dimensions().height as f32), |source| (source.width, source.height));
+            let (_, visible) = fit.place(size, rect);
+
+            rect_contains(&visible, x, y)
+        },
+        Object::NinePatch { rect, .. } => rect_contains(rect, x, y),
+        Object::Svg { svg, rect, fit, .. } => match measure_svg(svg) {
+            Some(size) => rect_contains(&fit.place(size, rect).1, x, y),
+            None => rect_contains(rect, x, y)
+        },
+        Object::BackdropBlur { rect, rounding, .. } => rounded_rect_contains(rect, rounding.as_ref(), x, y),
+        Object::Path { path, stroke, .. } => {
+            let polylines = flatten(path);
+
+            path_contains(&polylines, path.fill_rule, x, y)
+            || stroke.as_ref().is_some_and(|stroke| polylines_distance(&polylines, x, y) <= stroke.width / 2.0)
+        },
+        Object::Group { transform, children } => {
+            let (x, y) = transform.invert()?.map_point(x, y);
+
+            return hit_children(children, x, y, measure_text, measure_svg)
+        },
+        Object::Clip { shape, children } => {
+            let inside = match shape {
+                ClipShape::Rect(rect) => rect_contains(rect, x, y),
+                ClipShape::RoundedRect(rect, rounding) => rounded_rect_contains(rect, Some(rounding), x, y),
+                // Like Vello, clips always use the non-zero rule
+                ClipShape::Path(path) => path_contains(&flatten(path), FillRule::NonZero, x, y)
+            };
+            if !inside { return None }
+
+            return hit_children(children, x, y, measure_text, measure_svg)
+        },
+        Object::Layer { opacity, children, .. } => {
+            if *opacity <= 0.0 { return None }
+
+            return hit_children(children, x, y, measure_text, measure_svg)
+        },
+        Object::Tagged { id, object } => return hit_object(object, x, y, measure_text, measure_svg).map(|inner| inner.or(Some(*id)))
+    };
+
+    hit.then_some(None)
+}
+
+fn hit_children(children: &[Object], x: f32, y: f32, measure_text: MeasureText, measure_svg: MeasureSvg) -> Option<Option<u64>> {
+    children.iter().rev().find_map(|child| hit_object(child, x, y, measure_text, measure_svg))
+}
+
+/// Rounded corners stay rounded around a stroke, square ones stay square
+#[inline]
+fn grow_radius(radius: f32, amount: f32) -> f32 {
+    if radius > 0.0 { radius + amount } else { 0.0 }
+}
+
+#[inline]
+fn rect_contains(rect: &Rect, x: f32, y: f32) -> bool {
+    x >= rect.x && y >= rect.y && x < rect.x + rect.width && y < rect.y + rect.height
+}
+
+fn rounded_rect_contains(rect: &Rect, rounding: Option<&Rounding>, x: f32, y: f32) -> bool {
+    if !rect_contains(rect, x, y) { return false }
+    let Some(rounding) = rounding else { return true };
+
+    // Radii that don't fit are shrunk, like when drawing
+    let max_radius = rect.width.min(rect.height) / 2.0;
+    let (left, top, right, bottom) = (rect.x, rect.y, rect.x + rect.width, rect.y + rect.height);
+
+    [
+        (rounding.top_l, left, top),
+        (rounding.top_r, right, top),
+        (rounding.bottom_l, left, bottom),
+        (rounding.bottom_r, right, bottom)
+    ].into_iter().all(|(radius, corner_x, corner_y)| {
+        let radius = radius.min(max_radius);
+        if radius <= 0.0 { return true }
+
+        // The center of the corner's circle
+        let center_x = if corner_x == left { left + radius } else { right - radius };
+        let center_y = if corner_y == top { top + radius } else { bottom - radius };
+
+        let in_corner = (x - center_x) * (corner_x - center_x) > 0.0 && (y - center_y) * (corner_y - center_y) > 0.0;
+        !in_corner || (x - center_x).powi(2) + (y - center_y).powi(2) <= radius * radius
+    })
+}
+
+/// Turns the path into closed polylines, approximating curves with straight lines
+fn flatten(path: &Path) -> Vec<Vec<(f32, f32)>> {
+    let mut polylines: Vec<Vec<(f32, f32)>> = Vec::new();
+    let mut current = (0.0, 0.0);
+
+    let curve = |polyline: &mut Vec<(f32, f32)>, point: &dyn Fn(f32) -> (f32, f32)| {
+        polyline.extend((1..=CURVE_SEGMENTS).map(|step| point(step as f32 / CURVE_SEGMENTS as f32)));
+    };
+
+    for element in path.elements() {
+        // Drawing without a move starts at the end of the last polyline
+        if !matches!(element, PathElement::MoveTo(..)) && polylines.is_empty() {
+            polylines.push(vec![current]);
+        }
+
+        match element {
+            PathElement::MoveTo(x, y) => polylines.push(vec![(x, y)]),
+            PathElement::LineTo(x, y) => polylines.last_mut().unwrap().push((x, y)),
+            PathElement::QuadTo(cx, cy, x, y) => {
+                let (x0, y0) = current;
+                curve(polylines.last_mut().unwrap(), &|t| {
+                    let u = 1.0 - t;
+                    (u * u * x0 + 2.0 * u * t * cx + t * t * x, u * u * y0 + 2.0 * u * t * cy + t * t * y)
+                });
+            },
+            PathElement::CubicTo(cx1, cy1, cx2, cy2, x, y) => {
+                let (x0, y0) = current;
+                curve(polylines.last_mut().unwrap(), &|t| {
+                    let u = 1.0 - t;
+                    (
+                        u * u * u * x0 + 3.0 * u * u * t * cx1 + 3.0 * u * t * t * cx2 + t * t * t * x,
+                        u * u * u * y0 + 3.0 * u * u * t * cy1 + 3.0 * u * t * t * cy2 + t * t * t * y
+                    )
+                });
+            },
+            PathElement::Close => {
+                let polyline = polylines.last_mut().unwrap();
+                let start = polyline[0];
+                polyline.push(start);
+                polylines.push(vec![start]);
+            }
+        }
+
+        current = *polylines.last().unwrap().last().unwrap();
+    }
+
+    polylines
+}
+
+/// Whether the point is inside of the filled polylines, which are closed implicitly like when filling
+fn path_contains(polylines: &[Vec<(f32, f32)>], fill_rule: FillRule, x: f32, y: f32) -> bool {
+    let mut winding = 0;
+
+    for polyline in polylines {
+        let closing = [polyline[polyline.len() - 1], polyline[0]];
+
+        for segment in polyline.windows(2).chain(std::iter::once(&closing[..])) {
+            let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+            if (y0 <= y) == (y1 <= y) { continue }
+
+            // Where the segment crosses the horizontal line through the point
+            let crossing = x0 + (y - y0) / (y1 - y0) * (x1 - x0);
+            if crossing > x {
+                winding += if y1 > y0 { 1 } else { -1 };
+            }
+        }
+    }
+
+    match fill_rule {
+        FillRule::NonZero => winding != 0,
+        FillRule::EvenOdd => winding % 2 != 0
+    }
+}
+
+/// The shortest distance between the point and the outline of the polylines
+fn polylines_distance(polylines: &[Vec<(f32, f32)>], x: f32, y: f32) -> f32 {
+    polylines.iter()
+    .flat_map(|polyline| polyline.windows(2))
+    .map(|segment| {
+        let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let length = dx * dx + dy * dy;
+
+        let t = if length == 0.0 { 0.0 } else { (((x - x0) * dx + (y - y0) * dy) / length).clamp(0.0, 1.0) };
+        ((x - x0 - t * dx).powi(2) + (y - y0 - t * dy).powi(2)).sqrt()
+    })
+    .fold(f32::INFINITY, f32::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::renderer::{objects::{ClipShape, Fit, FitMode, Object}, svgs::CacheableSvg, transforms::Transform};
+
+    use super::{hit_test, Hit};
+
+    fn square(x: f32, y: f32) -> Object {
+        Object::rectangle(x, y, 10.0, 10.0, 0xFFFFFFFF, None)
+    }
+
+    /// Every SVG is measured as 20x10
+    fn hit(objects: &[Object], x: f32, y: f32) -> Option<Hit> {
+        hit_test(&objects.iter().collect::<Vec<_>>(), x, y, &|_, _, _| None, &|_| Some((20.0, 10.0)))
+    }
+
+    #[test]
+    fn topmost_object_is_hit() {
+        let objects = [square(0.0, 0.0), square(5.0, 5.0)];
+
+        assert_eq!(hit(&objects, 7.0, 7.0), Some(Hit { index: 1, id: None }));
+        assert_eq!(hit(&objects, 2.0, 2.0), Some(Hit { index: 0, id: None }));
+        assert_eq!(hit(&objects, 20.0, 20.0), None);
+    }
+
+    #[test]
+    fn innermost_id_is_returned() {
+        let objects = [Object::group(Transform::IDENTITY, vec![
+            square(0.0, 0.0).with_id(2),
+            square(20.0, 0.0)
+        ]).with_id(1)];
+
+        assert_eq!(hit(&objects, 5.0, 5.0), Some(Hit { index: 0, id: Some(2) }));
+        // Untagged children are reported with the id of their tagged parent
+        assert_eq!(hit(&objects, 25.0, 5.0), Some(Hit { index: 0, id: Some(1) }));
+    }
+
+    #[test]
+    fn groups_are_transformed() {
+        // The square covers (100, 0) to (120, 20)
+        let objects = [Object::group(Transform::scale(2.0).then(Transform::translate(100.0, 0.0)), vec![square(0.0, 0.0)])];
+
+        assert_eq!(hit(&objects, 5.0, 5.0), None);
+        assert_eq!(hit(&objects, 125.0, 5.0), None);
+        assert_eq!(hit(&objects, 115.0, 15.0), Some(Hit { index: 0, id: None }));
+    }
+
+    #[test]
+    fn clips_reject_points_outside() {
+        let objects = [Object::clip(ClipShape::Rect(Object::rect(0.0, 0.0, 5.0, 5.0)), vec![square(0.0, 0.0)])];
+
+        assert_eq!(hit(&objects, 2.0, 2.0), Some(Hit { index: 0, id: None }));
+        assert_eq!(hit(&objects, 7.0, 7.0), None);
+    }
+
+    #[test]
+    fn svgs_are_hit_where_they_are_placed() {
+        let svg = CacheableSvg::new_cloned(b"<svg/>");
+        let objects = [Object::svg(0.0, 0.0, 40.0, 40.0, svg, 0xFFFFFFFF).with_fit(Fit::new(FitMode::Contain))];
+
+        // Contained and centered, the SVG covers (0, 10) to (40, 30)
+        assert_eq!(hit(&objects, 20.0, 5.0), None);
+        assert_eq!(hit(&objects, 20.0, 20.0), Some(Hit { index: 0, id: None }));
+        assert_eq!(hit(&objects, 20.0, 35.0), None);
+    }
+}
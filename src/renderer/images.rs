@@ -1,8 +1,8 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use uuid::Uuid;
 
-use crate::structs::Dimensions;
+use crate::{backend::renderer_data::{RendererData, RendererDataTrait}, structs::Dimensions};
 
 
 /// ### A cheap to clone, decoded Image container.
@@ -20,6 +20,7 @@ pub struct CacheableImage {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PixelFormat {
     RGB8,
     RGBA8,
@@ -101,4 +102,187 @@ impl CacheableImage {
     pub fn uuid(&self) -> &Uuid {
         &self.uuid
     }
-}
\ No newline at end of file
+}
+
+/// Images are compared by their Uuid, so comparing never looks at the pixels
+impl PartialEq for CacheableImage {
+    fn eq(&self, other: &Self) -> bool {
+        self.uuid == other.uuid
+    }
+}
+
+/// Inside of a capture, the pixels are only written once and later frames refer to the image by its Uuid
+#[cfg(feature = "serde")]
+impl serde::Serialize for CacheableImage {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        super::capture::serialize_asset(self.uuid, (&self.pixels, &self.format, &self.dimensions), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CacheableImage {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        super::capture::deserialize_asset(deserializer, |uuid, (pixels, format, dimensions)| Self { pixels, format, dimensions, uuid })
+    }
+}
+
+
+/// ### A cheap to clone, decoded animation, like a GIF.
+/// Every frame is its own `CacheableImage`, so renderers only load each frame once.
+/// Draw the current frame with `Object::image`, by getting it from `frame_at`.
+#[derive(Debug, Clone)]
+pub struct AnimatedImage {
+    frames: Arc<[AnimationFrame]>,
+    duration: Duration
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimationFrame {
+    pub image: CacheableImage,
+    /// How long the frame is shown
+    pub delay: Duration
+}
+
+impl AnimatedImage {
+    /// Frames without a delay are shown for this long instead, like browsers do
+    const DEFAULT_DELAY: Duration = Duration::from_millis(100);
+
+    /// Panics if there are no frames.
+    pub fn new(frames: Vec<AnimationFrame>) -> Self {
+        assert!(!frames.is_empty(), "An animated image needs at least one frame!");
+
+        let frames: Arc<[AnimationFrame]> = frames
+        .into_iter()
+        .map(|frame| AnimationFrame {
+            delay: if frame.delay.is_zero() { Self::DEFAULT_DELAY } else { frame.delay },
+            ..frame
+        })
+        .collect();
+        let duration = frames.iter().map(|frame| frame.delay).sum();
+
+        Self { frames, duration }
+    }
+
+    /// ### Decodes all frames of a GIF, APNG or animated WebP, using the `image` crate.
+    /// Other images are decoded into a single frame.
+    /// Returns an error if the image is truncated, malformed or in an unsupported format.
+    #[cfg(feature = "image")]
+    pub fn from_encoded(bytes: &[u8]) -> image::ImageResult<Self> {
+        use std::io::Cursor;
+        use image::{codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder}, AnimationDecoder, ImageFormat};
+
+        let frames = match image::guess_format(bytes) {
+            Ok(ImageFormat::Gif) => Some(GifDecoder::new(Cursor::new(bytes))?.into_frames()),
+            Ok(ImageFormat::Png) => {
+                let decoder = PngDecoder::new(Cursor::new(bytes))?;
+                if decoder.is_apng()? { Some(decoder.apng()?.into_frames()) } else { None }
+            },
+            Ok(ImageFormat::WebP) => {
+                let decoder = WebPDecoder::new(Cursor::new(bytes))?;
+                decoder.has_animation().then(|| decoder.into_frames())
+            },
+            _ => None
+        };
+
+        let frames: Vec<AnimationFrame> = match frames {
+            Some(frames) => frames
+            .map(|frame| frame.map(|frame| {
+                let delay = Duration::from(frame.delay());
+
+                AnimationFrame {
+                    image: CacheableImage::from_image(frame.into_buffer().into()),
+                    delay
+                }
+            }))
+            .collect::<Result<_, _>>()?,
+            None => Vec::new()
+        };
+
+        // Still images, and animations without any frames, are decoded as a single frame
+        if frames.is_empty() {
+            let image = CacheableImage::from_image(image::load_from_memory(bytes)?);
+            return Ok(Self::new(vec![AnimationFrame { image, delay: Duration::ZERO }]))
+        }
+
+        Ok(Self::new(frames))
+    }
+
+    /// The frame that is shown after the animation has been playing for `elapsed`, looping forever
+    pub fn frame_at(&self, elapsed: Duration) -> &CacheableImage {
+        let mut remaining = Duration::from_nanos((elapsed.as_nanos() % self.duration.as_nanos().max(1)) as u64);
+
+        for frame in self.frames.iter() {
+            if remaining < frame.delay {
+                return &frame.image
+            }
+            remaining -= frame.delay;
+        }
+
+        &self.frames[self.frames.len() - 1].image
+    }
+
+    pub fn frames(&self) -> &[AnimationFrame] {
+        &self.frames
+    }
+
+    /// How long it takes to play every frame once
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn is_animated(&self) -> bool {
+        self.frames.len() > 1
+    }
+
+    /// The dimensions of the first frame, which all frames share when decoded with `from_encoded`
+    pub fn dimensions(&self) -> &Dimensions<u32> {
+        self.frames[0].image.dimensions()
+    }
+
+    /// Removes every frame from the renderer's image cache
+    pub fn unload(&self, data: &RendererData) {
+        for frame in self.frames.iter() {
+            data.unload_image(&frame.image);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use crate::structs::Dimensions;
+
+    use super::{AnimatedImage, AnimationFrame, CacheableImage, PixelFormat};
+
+    fn frame(delay: u64) -> AnimationFrame {
+        AnimationFrame {
+            image: CacheableImage::new(Arc::from([0u8; 4]), PixelFormat::RGBA8, Dimensions::new(1, 1)),
+            delay: Duration::from_millis(delay)
+        }
+    }
+
+    #[test]
+    fn frame_at_loops() {
+        // The last frame has no delay, so it gets the default one
+        let image = AnimatedImage::new(vec![frame(100), frame(200), frame(0)]);
+        let frames = image.frames();
+        let at = |millis: u64| image.frame_at(Duration::from_millis(millis));
+
+        assert_eq!(image.duration(), Duration::from_millis(400));
+        assert_eq!(at(0), &frames[0].image);
+        assert_eq!(at(99), &frames[0].image);
+        assert_eq!(at(100), &frames[1].image);
+        assert_eq!(at(350), &frames[2].image);
+        assert_eq!(at(400), &frames[0].image);
+        assert_eq!(at(1_350), &frames[1].image);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn malformed_animations_are_errors() {
+        assert!(AnimatedImage::from_encoded(b"GIF89a\x01\x00").is_err());
+        assert!(AnimatedImage::from_encoded(b"\x89PNG\r\n\x1a\n").is_err());
+        assert!(AnimatedImage::from_encoded(b"not an image").is_err());
+    }
+}
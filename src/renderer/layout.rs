@@ -0,0 +1,378 @@
+use std::num::NonZeroU32;
+
+use skrifa::{instance::{Location, Size}, metrics::GlyphMetrics, FontRef, GlyphId, MetadataProvider};
+use text_layout::{Item, ParagraphLayout};
+
+use crate::types::{Brush, Dimensions, FontMetrics, Object, TextAlign, TextDecoration, TextMetrics, TextOptions, TextOverflow, TextSpan, TextWrap};
+
+
+/// A glyph, positioned relative to the top left corner of the laid out text.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PositionedGlyph {
+    pub id: u32,
+    pub x: f32,
+    pub y: f32
+}
+
+/// The result of laying out text with a font through `skrifa`.
+/// Shared between the renderers that do their own text layout (Vello and tiny-skia).
+#[derive(Debug, Clone)]
+pub(crate) struct GlyphLayout {
+    pub glyphs: Vec<PositionedGlyph>,
+    pub location: Location,
+    pub size: f32,
+    pub dimensions: Dimensions<f32>
+}
+
+/// The options `Object::Text` is laid out with, the default ones at its size
+pub(crate) fn text_object_options(size: f32) -> TextOptions {
+    TextOptions { size, ..TextOptions::default() }
+}
+
+/// Lays out a single run of text with the weight, style and spacing of the options, only breaking lines at `\n`.
+pub(crate) fn layout_text(font_ref: &FontRef, text: &str, options: &TextOptions) -> GlyphLayout {
+    let var_loc = font_location(font_ref, options.weight, options.italic);
+    let charmap = font_ref.charmap();
+    let line_height = font_metrics(font_ref, options.size, &var_loc).line_height;
+    let glyph_metrics = font_ref.glyph_metrics(Size::new(options.size), &var_loc);
+
+    let mut pen_x = 0.0;
+    let mut pen_y = line_height;
+    let mut dimensions = Dimensions::new(0.0f32, line_height);
+
+    let glyphs = text.chars().filter_map(|ch| {
+        if ch == '\n' {
+            pen_y += line_height;
+            pen_x = 0.0;
+            dimensions.height += line_height;
+            return None;
+        }
+        let gid = charmap.map(ch).unwrap_or_default();
+        let x = pen_x;
+        pen_x += advance(&glyph_metrics, gid, ch, options);
+        dimensions.width = dimensions.width.max(pen_x);
+
+        Some(PositionedGlyph {
+            id: gid.to_u32(),
+            x,
+            y: pen_y,
+        })
+    }).collect();
+
+    GlyphLayout { glyphs, location: var_loc, size: options.size, dimensions }
+}
+
+/// How far a character moves the next one, including the letter and word spacing of the options
+fn advance(glyph_metrics: &GlyphMetrics, gid: GlyphId, ch: char, options: &TextOptions) -> f32 {
+    let advance = glyph_metrics.advance_width(gid).unwrap_or_default() + options.letter_spacing;
+    if ch.is_whitespace() { advance + options.word_spacing } else { advance }
+}
+
+/// The decoration lines of text drawn with `layout_text` at (x, y), built as objects
+pub(crate) fn text_decorations(font_ref: &FontRef, text: &str, options: &TextOptions, x: f32, y: f32, decoration: &TextDecoration, brush: &Brush) -> Vec<Object> {
+    let metrics = font_metrics(font_ref, options.size, &font_location(font_ref, options.weight, options.italic));
+
+    text.split('\n').enumerate().flat_map(|(index, line)| {
+        let width = layout_text(font_ref, line, options).dimensions.width;
+        // Like the glyphs, every line has its baseline at its bottom
+        decoration.objects(x, y + metrics.line_height * (index + 1) as f32, width, &metrics, brush)
+    }).collect()
+}
+
+/// A paragraph laid out from spans, with one glyph layout per span in the same order.
+/// The glyphs of every run are positioned relative to the top left corner of the paragraph, which is also their dimensions.
+#[derive(Debug, Clone)]
+pub(crate) struct ParagraphGlyphs {
+    pub runs: Vec<GlyphLayout>,
+    /// The decoration lines of the spans that have them, in paragraph coordinates
+    pub decorations: Vec<Object>,
+    pub dimensions: Dimensions<f32>
+}
+
+/// A character of a paragraph, before it is placed on a line
+#[derive(Debug, Clone, Copy)]
+struct ParagraphChar {
+    span: usize,
+    id: GlyphId,
+    advance: f32,
+    ch: char
+}
+
+/// Lays out a paragraph with the Knuth-Plass line breaking algorithm, wrapping at `width`.
+/// Every span is laid out with its own font, and a line is as high as its highest span.
+/// Lines that don't fit into `max_height` or `options.max_lines` are cut off, eliding the last one if the overflow is `TextOverflow::Elide`.
+pub(crate) fn layout_paragraph(spans: &[(&FontRef, &TextSpan)], width: u32, max_height: Option<NonZeroU32>, options: &TextOptions) -> ParagraphGlyphs {
+    let should_layout = options.wrap == TextWrap::WordWrap;
+    let text_len = spans.iter().map(|(_, span)| span.text.len()).sum::<usize>();
+
+    let layout: text_layout::KnuthPlass<f32> = text_layout::KnuthPlass::new().with_threshold(f32::INFINITY);
+    // The first item is the indent, so the items are one ahead of the characters
+    let mut layout_items: Option<Vec<Item<()>>> = should_layout.then(|| {
+        let mut items = Vec::with_capacity(text_len + 1);
+        items.push(Item::Box { width: options.indent, data: () });
+        items
+    });
+
+    let mut runs = Vec::with_capacity(spans.len());
+    let mut line_heights = Vec::with_capacity(spans.len());
+    let mut span_metrics = Vec::with_capacity(spans.len());
+    let mut chars: Vec<ParagraphChar> = Vec::with_capacity(text_len);
+    // The glyph id and advance of "…" in the font of every span
+    let mut ellipses = Vec::with_capacity(spans.len());
+
+    for (index, (font_ref, span)) in spans.iter().enumerate() {
+        let var_loc = font_location(font_ref, span.style.weight, span.style.italic);
+
+        let font_size = Size::new(span.style.size);
+        let charmap = font_ref.charmap();
+        let metrics = font_metrics(font_ref, span.style.size, &var_loc);
+        let glyph_metrics = font_ref.glyph_metrics(font_size, &var_loc);
+        line_heights.push(match options.line_height {
+            Some(line_height) => line_height * span.style.size,
+            None => metrics.line_height
+        });
+        span_metrics.push(metrics);
+
+        for ch in span.text.chars() {
+            let gid = charmap.map(ch).unwrap_or_default();
+            let advance = match ch {
+                // Where a tab ends depends on where it starts, so it is broken as if it took a whole tab width
+                '\t' => options.tab_width,
+                _ => advance(&glyph_metrics, gid, ch, options)
+            };
+
+            if let Some(items) = &mut layout_items {
+                items.push(if ch.is_whitespace() && !chars.is_empty() {
+                    Item::Glue {
+                        width: advance,
+                        stretch: 1.0,
+                        shrink: 0.0,
+                        data: (),
+                    }
+                } else {
+                    Item::Box {
+                        width: advance,
+                        data: (),
+                    }
+                });
+            }
+
+            chars.push(ParagraphChar { span: index, id: gid, advance, ch });
+        }
+
+        let ellipsis = charmap.map('…').unwrap_or_default();
+        ellipses.push((ellipsis, glyph_metrics.advance_width(ellipsis).unwrap_or_default() + options.letter_spacing));
+
+        runs.push(GlyphLayout { glyphs: Vec::new(), location: var_loc, size: span.style.size, dimensions: Dimensions::new(0.0, 0.0) });
+    }
+
+    let breaks: Vec<usize> = if let Some(items) = layout_items {
+        layout.layout_paragraph(items.as_slice(), width as f32)
+        .iter()
+        .map(|line_break| line_break.break_at.saturating_sub(1))
+        .collect()
+    } else {
+        Vec::new()
+    };
+
+    let width = width as f32;
+    let line_height_of = |line: &[ParagraphChar]| if line.is_empty() {
+        // An empty paragraph still has the height of one line
+        line_heights.iter().copied().fold(0.0, f32::max)
+    } else {
+        line.iter().map(|ch| line_heights[ch.span]).fold(0.0, f32::max)
+    };
+
+    let mut line_start = 0;
+    let mut lines: Vec<Vec<ParagraphChar>> = breaks.into_iter().chain(std::iter::once(chars.len())).enumerate().map(|(line_index, line_end)| {
+        // The whitespace a line was broken at isn't part of any line
+        let line = chars[(line_start + usize::from(line_index > 0)).min(line_end)..line_end].to_vec();
+        line_start = line_end;
+        line
+    }).collect();
+
+    // Like Skia, a paragraph always shows at least one line
+    let max_lines = options.max_lines.map_or(usize::MAX, |max_lines| max_lines.get() as usize);
+    let mut visible_height = 0.0;
+    let visible_lines = lines.iter().take_while(|line| {
+        let line_height = line_height_of(&line[..]);
+        let fits = visible_height == 0.0 || max_height.is_none_or(|max_height| visible_height + line_height <= max_height.get() as f32);
+        if fits { visible_height += line_height; }
+        fits
+    }).count().clamp(1, max_lines);
+
+    let cut_off = visible_lines < lines.len();
+    lines.truncate(visible_lines);
+
+    // Unwrapped lines can also be too long, but are only elided if the paragraph is limited, like in Skia
+    let limited = max_height.is_some() || options.max_lines.is_some();
+    if let (TextOverflow::Elide, Some(last)) = (&options.overflow, lines.last_mut()) {
+        let indent = if visible_lines == 1 { options.indent } else { 0.0 };
+        if cut_off || (limited && place_line(last, indent, 0.0, options.tab_width).1 > width) {
+            elide(last, indent, width, &ellipses, options.tab_width);
+        }
+    }
+
+    let line_count = lines.len();
+    let mut pen_y = 0.0;
+    let mut decorations = Vec::new();
+
+    for (line_index, line) in lines.iter().enumerate() {
+        pen_y += line_height_of(&line[..]);
+
+        let indent = if line_index == 0 { options.indent } else { 0.0 };
+        let (mut positions, line_width) = place_line(line, indent, 0.0, options.tab_width);
+
+        let free = (width - line_width).max(0.0);
+        let spaces = line.iter().filter(|ch| ch.ch.is_whitespace() && ch.ch != '\t').count();
+
+        match options.align {
+            TextAlign::Left => {},
+            TextAlign::Center => positions.iter_mut().for_each(|x| *x += free / 2.0),
+            TextAlign::Right => positions.iter_mut().for_each(|x| *x += free),
+            // The last line keeps its natural spacing
+            TextAlign::Justify => if line_index + 1 < line_count && spaces > 0 {
+                positions = place_line(line, indent, free / spaces as f32, options.tab_width).0;
+            }
+        }
+
+        // Every run of characters from the same span gets one decoration, from the start of its first character to the end of its last one
+        let mut start = 0;
+        for end in 1..=line.len() {
+            if end < line.len() && line[end].span == line[start].span { continue }
+
+            let span = line[start].span;
+            if let Some(decoration) = &spans[span].1.style.decoration {
+                let x = positions[start];
+                let end_x = positions.get(end).copied().unwrap_or(positions[end - 1] + line[end - 1].advance);
+                decorations.extend(decoration.objects(x, pen_y, end_x - x, &span_metrics[span], &spans[span].1.style.brush));
+            }
+            start = end;
+        }
+
+        for (ch, x) in line.iter().zip(positions) {
+            if ch.ch == '\t' { continue }
+
+            runs[ch.span].glyphs.push(PositionedGlyph {
+                id: ch.id.to_u32(),
+                x,
+                y: pen_y
+            });
+        }
+    }
+
+    let dimensions = Dimensions::new(width, pen_y);
+    for run in &mut runs {
+        run.dimensions = dimensions;
+    }
+
+    ParagraphGlyphs { runs, decorations, dimensions }
+}
+
+/// Removes characters from the end of the line until "…" fits behind them, then adds it in the font of the last remaining one
+fn elide(line: &mut Vec<ParagraphChar>, start: f32, width: f32, ellipses: &[(GlyphId, f32)], tab_width: f32) {
+    let Some(first_span) = line.first().map(|ch| ch.span) else { return };
+    let (positions, line_width) = place_line(line, start, 0.0, tab_width);
+
+    let kept = (1..=line.len()).rev().find(|&count| {
+        let last = &line[count - 1];
+        let end = positions.get(count).copied().unwrap_or(line_width);
+        !last.ch.is_whitespace() && end + ellipses[last.span].1 <= width
+    }).unwrap_or(0);
+
+    line.truncate(kept);
+    let span = line.last().map_or(first_span, |ch| ch.span);
+    let (id, advance) = ellipses[span];
+    line.push(ParagraphChar { span, id, advance, ch: '…' });
+}
+
+/// The x position of every character on a line starting at `start`, and where the line ends.
+/// Every whitespace character grows by `extra_space`, tabs move to the next multiple of `tab_width`.
+fn place_line(line: &[ParagraphChar], start: f32, extra_space: f32, tab_width: f32) -> (Vec<f32>, f32) {
+    let mut pen_x = start;
+
+    let positions = line.iter().map(|ch| {
+        let x = pen_x;
+
+        pen_x = match ch.ch {
+            '\t' if tab_width > 0.0 => ((pen_x / tab_width).floor() + 1.0) * tab_width,
+            '\t' => pen_x,
+            space if space.is_whitespace() => pen_x + ch.advance + extra_space,
+            _ => pen_x + ch.advance
+        };
+
+        x
+    }).collect();
+
+    (positions, pen_x)
+}
+
+/// Measures text with the weight, style and spacing of the options, only breaking lines at `\n` like `layout_text`.
+pub(crate) fn measure_text(font_ref: &FontRef, text: &str, options: &TextOptions) -> TextMetrics {
+    let metrics = font_metrics(font_ref, options.size, &font_location(font_ref, options.weight, options.italic));
+    let dimensions = layout_text(font_ref, text, options).dimensions;
+
+    TextMetrics {
+        width: dimensions.width,
+        height: dimensions.height,
+        ascent: metrics.ascent,
+        descent: metrics.descent,
+        // Glyphs are placed with their baseline at the bottom of the line
+        baseline: metrics.line_height
+    }
+}
+
+pub(crate) fn font_metrics(font_ref: &FontRef, size: f32, location: &Location) -> FontMetrics {
+    let metrics = font_ref.metrics(Size::new(size), location);
+
+    // Positions are negative in skrifa if they are below the baseline.
+    // Fonts without decoration metrics get lines of a twelfth of their size, below the baseline and around the middle of lowercase letters.
+    let underline = metrics.underline.map_or((-metrics.descent / 2.0, size / 12.0), |underline| (-underline.offset, underline.thickness));
+    let strikeout = metrics.strikeout.map_or((-metrics.ascent * 0.3, size / 12.0), |strikeout| (-strikeout.offset, strikeout.thickness));
+
+    FontMetrics {
+        ascent: metrics.ascent,
+        descent: -metrics.descent,
+        leading: metrics.leading,
+        line_height: metrics.ascent - metrics.descent + metrics.leading,
+        underline_offset: underline.0,
+        underline_thickness: underline.1,
+        strikeout_offset: strikeout.0,
+        strikeout_thickness: strikeout.1
+    }
+}
+
+/// The variation axes for the weight and style
+fn font_location(font_ref: &FontRef, weight: u32, italic: bool) -> Location {
+    font_ref.axes().location::<&[(&str, f32)]>(&[
+        ("wght", weight as f32),
+        ("ital", if italic { 1.0 } else { 0.0 }),
+        ("slnt", if italic { -45.0 } else { 0.0 }) // TODO: Test this
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::FontRef;
+
+    use crate::types::TextOptions;
+
+    use super::{layout_text, measure_text, text_object_options};
+
+    const FONT: &[u8] = include_bytes!("../../examples/JetBrains_Mono.ttf");
+
+    #[test]
+    fn spacing_is_measured_like_it_is_laid_out() {
+        let font_ref = FontRef::new(FONT).unwrap();
+        let plain = layout_text(&font_ref, "a b\nab", &text_object_options(16.0)).dimensions.width;
+        let options = TextOptions { size: 16.0, letter_spacing: 2.0, word_spacing: 5.0, ..TextOptions::default() };
+
+        let layout = layout_text(&font_ref, "a b\nab", &options);
+        // Three characters with letter spacing, one of them a space with word spacing
+        assert_eq!(layout.dimensions.width, plain + 3.0 * 2.0 + 5.0);
+        assert_eq!(layout.glyphs[2].x - layout.glyphs[1].x, layout.glyphs[1].x - layout.glyphs[0].x + 5.0);
+
+        let metrics = measure_text(&font_ref, "a b\nab", &options);
+        assert_eq!((metrics.width, metrics.height), (layout.dimensions.width, layout.dimensions.height));
+    }
+}
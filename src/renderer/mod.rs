@@ -5,24 +5,42 @@ use strum::{EnumIter, IntoEnumIterator};
 
 pub mod errors;
 pub mod objects;
+pub(crate) mod overlay;
+pub mod brushes;
+pub mod hit_test;
 pub mod images;
+pub mod paths;
+pub mod scene;
+pub mod strokes;
 pub mod svgs;
 pub mod text;
+pub mod transforms;
 
+#[cfg(feature = "serde")]
+pub mod capture;
+#[cfg(any(feature = "r-vello", feature = "r-tiny-skia"))]
+pub(crate) mod layout;
+#[cfg(any(feature = "r-vello", feature = "r-tiny-skia"))]
+pub(crate) mod blur;
 #[cfg(feature = "r-wgpu")]
 pub mod wgpu;
 #[cfg(feature = "r-vello")]
 pub mod vello;
 #[cfg(feature = "r-skia")]
 pub mod skia;
+#[cfg(feature = "r-tiny-skia")]
+pub mod tiny_skia;
 
 
-use crate::{backend::{renderer_data::RendererData, windowing::window::Window}, types::{Backend, Object}};
+use crate::{backend::{renderer_data::RendererData, windowing::window::{Window, WindowTrait}}, types::{Backend, Object}};
 
-use self::errors::RendererError;
+use self::{errors::RendererError, scene::{Damage, RetainedScene}};
 
 pub type RResult<T> = core::result::Result<T, RendererError>;
 
+/// The color renderers clear the window with, until `RendererTrait::set_clear_color` is called
+pub const DEFAULT_CLEAR_COLOR: u32 = 0x1A1A1AFF;
+
 
 #[derive(Debug, EnumIter, Clone, Copy)]
 pub enum RendererType {
@@ -32,6 +50,8 @@ pub enum RendererType {
     Vello,
     #[cfg(feature = "r-skia")]
     Skia,
+    #[cfg(feature = "r-tiny-skia")]
+    TinySkia,
 }
 
 impl Default for RendererType {
@@ -49,6 +69,8 @@ pub enum Renderer {
     Vello(self::vello::VelloRenderer),
     #[cfg(feature = "r-skia")]
     Skia(self::skia::SkiaRenderer),
+    #[cfg(feature = "r-tiny-skia")]
+    TinySkia(self::tiny_skia::TinySkiaRenderer),
 }
 
 impl Renderer {
@@ -80,6 +102,10 @@ impl Renderer {
             #[cfg(feature = "r-skia")]
             RendererType::Skia => {
                 self::skia::SkiaRenderer::new(window)?.into()
+            },
+            #[cfg(feature = "r-tiny-skia")]
+            RendererType::TinySkia => {
+                self::tiny_skia::TinySkiaRenderer::new(window)?.into()
             }
         })
     }
@@ -90,4 +116,25 @@ pub trait RendererTrait {
     fn render(&self, window: &Window, data: &RendererData, objects: Vec<&Object>) -> RResult<()>;
     fn recreate(&self, window: &Window, data: &RendererData);
     fn transform_data(&self, data: &RendererData) -> Option<RendererData>;
+    /// Sets the RGBA color the window is cleared with before drawing.
+    /// Transparent colors only show what is behind the window if it was created with `WindowDetails::transparent`.
+    fn set_clear_color(&self, color: u32);
+    fn clear_color(&self) -> u32;
+    /// Draws FPS, a frame time graph, the object count and the bounds of every object on top of each frame.
+    /// Enabled from the start if the `LUMI_DEBUG_OVERLAY` environment variable is set.
+    fn set_debug_overlay(&self, enabled: bool);
+    fn debug_overlay(&self) -> bool;
+
+    /// Renders a retained scene, skipping the frame if nothing changed since it was last drawn.
+    /// tiny-skia and Skia keep the last frame, so they only redraw the damaged regions, and tiny-skia also only presents those.
+    /// Vello draws and presents the whole scene again whenever anything changed.
+    fn render_scene(&self, window: &Window, data: &RendererData, scene: &mut RetainedScene) -> RResult<()> {
+        let (dimensions, scale, clear_color) = (window.physical_dimensions(), window.current_scale(), self.clear_color());
+        // The debug overlay changes every frame
+        if !self.debug_overlay() && scene.damage(dimensions, scale, clear_color, |object| data.object_bounds(object)) == Damage::None { return Ok(()) }
+
+        self.render(window, data, scene.objects())?;
+        scene.commit(dimensions, scale, clear_color);
+        Ok(())
+    }
 }
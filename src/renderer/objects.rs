@@ -1,52 +1,356 @@
-use std::ops::Mul;
+use std::{ops::Mul, time::Duration};
 
-use crate::{backend::windowing::window::{WindowTrait, Window}, structs::Position};
+use crate::{backend::windowing::window::{WindowTrait, Window}, structs::{Dimensions, Position}};
 
-use super::{images::CacheableImage, svgs::CacheableSvg, text::Paragraph};
+use super::{brushes::Brush, images::{AnimatedImage, CacheableImage}, paths::Path, strokes::Stroke, svgs::CacheableSvg, text::{Paragraph, ParagraphTrait, TextDecoration, TextWrap}, transforms::Transform};
 
-#[derive(Debug)]
+/// Measures `Object::Text` as (width, height) from its text, font and size
+pub(crate) type MeasureText<'a> = &'a dyn Fn(&str, &Option<String>, f32) -> Option<(f32, f32)>;
+/// Measures the size of an SVG as (width, height), like it is fitted into the rect of `Object::Svg`
+pub(crate) type MeasureSvg<'a> = &'a dyn Fn(&CacheableSvg) -> Option<(f32, f32)>;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Object {
-    Rectangle { rounding: Option<Rounding>, color: u32, rect: Rect },
-    Text { text: String, font: Option<String>, size: f32, color: u32, position: Position<i32>},
-    Paragraph { paragraph: Paragraph, position: Position<i32> },
-    Image { image: CacheableImage, rect: Rect },
-    Svg { svg: CacheableSvg, color: u32, rect: Rect }
+    Rectangle { rounding: Option<Rounding>, brush: Brush, rect: Rect, stroke: Option<Stroke>, shadow: Option<Shadow> },
+    Text { text: String, font: Option<String>, size: f32, brush: Brush, position: Position<f32>, decoration: Option<TextDecoration> },
+    Paragraph { paragraph: Paragraph, position: Position<f32> },
+    /// Draws the `source` part of the image, or all of it, fitted into the rect
+    Image { image: CacheableImage, rect: Rect, source: Option<Rect>, fit: Fit },
+    /// Draws a 9-slice image, where the corners keep their size while the edges and the center are stretched.
+    /// `scale` is the size of the corners relative to the image's pixels, like 0.5 for a @2x asset.
+    NinePatch { image: CacheableImage, rect: Rect, insets: Insets, scale: f32 },
+    /// The SVG is tinted with the brush, keeping its alpha
+    Svg { svg: CacheableSvg, brush: Brush, rect: Rect, fit: Fit },
+    Path { path: Path, brush: Brush, stroke: Option<Stroke> },
+    /// Draws the children in order, transformed as a unit
+    Group { transform: Transform, children: Vec<Object> },
+    /// Draws the children in order, only where they are inside of the shape
+    Clip { shape: ClipShape, children: Vec<Object> },
+    /// Draws the children on their own, then composites them as a whole with the given opacity and blend mode
+    Layer { opacity: f32, blend: BlendMode, children: Vec<Object> },
+    /// Blurs everything that was drawn before it inside of the rect, then fills it with the tint, if there is one.
+    /// Like in CSS `backdrop-filter`, `blur` is the standard deviation of the gaussian blur.
+    BackdropBlur { rect: Rect, rounding: Option<Rounding>, blur: f32, tint: Option<Brush> },
+    /// Draws the object as it is, giving it an id that is returned by hit testing
+    Tagged { id: u64, object: Box<Object> }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClipShape {
+    Rect(Rect),
+    RoundedRect(Rect, Rounding),
+    /// Vello always uses `FillRule::NonZero` for clipping
+    Path(Path)
+}
+
+/// How a layer is blended with what is behind it.
+/// See the [CSS definitions](https://www.w3.org/TR/compositing-1/#blending) for their effects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity
+}
+
+/// A box shadow, drawn behind a rectangle
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Shadow {
+    pub x: f32,
+    pub y: f32,
+    /// Like in CSS, the blur radius is twice the standard deviation of the gaussian blur
+    pub blur: f32,
+    /// Grows (or shrinks, if negative) the shadow in every direction
+    pub spread: f32,
+    pub color: u32
+}
+
+impl Shadow {
+    #[inline]
+    pub fn new(x: f32, y: f32, blur: f32, spread: f32, color: u32) -> Self {
+        Self { x, y, blur, spread, color }
+    }
+
+    /// The shadow's rect as (x, y, width, height) and its corner radii (clockwise, starting at the top left),
+    /// before blurring.
+    pub(crate) fn shape(&self, rect: &Rect, rounding: Option<&Rounding>) -> ((f32, f32, f32, f32), [f32; 4]) {
+        let (x, y) = (rect.x + self.x - self.spread, rect.y + self.y - self.spread);
+        let (width, height) = (
+            (rect.width + self.spread * 2.0).max(0.0),
+            (rect.height + self.spread * 2.0).max(0.0)
+        );
+
+        let radii = rounding.map_or([0.0; 4], |r| [r.top_l, r.top_r, r.bottom_r, r.bottom_l].map(|radius| {
+            if radius == 0.0 { 0.0 } else { (radius + self.spread).max(0.0) }
+        }));
+
+        ((x, y, width, height), radii)
+    }
+}
+
+impl Mul<f32> for Shadow {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, with: f32) -> Self::Output {
+        Self {
+            x: self.x * with,
+            y: self.y * with,
+            blur: self.blur * with,
+            spread: self.spread * with,
+            color: self.color
+        }
+    }
+}
+
+/// How an image or SVG is resized to fit its rect, like CSS `object-fit`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FitMode {
+    /// Stretches it to the size of the rect
+    #[default]
+    Fill,
+    /// Scales it to be as large as possible while staying inside of the rect, keeping its aspect ratio
+    Contain,
+    /// Scales it to cover the whole rect, keeping its aspect ratio and cutting off what is outside
+    Cover,
+    /// Keeps its original size
+    None,
+    /// Uses `None` or `Contain`, whichever results in the smaller size
+    ScaleDown
+}
+
+/// Places an image or SVG in its rect, like CSS `object-fit` and `object-position`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fit {
+    pub mode: FitMode,
+    /// Where the content is placed if it doesn't exactly fill the rect, from 0.0 (left) to 1.0 (right)
+    pub align_x: f32,
+    /// Where the content is placed if it doesn't exactly fill the rect, from 0.0 (top) to 1.0 (bottom)
+    pub align_y: f32
 }
 
-#[derive(Debug, Clone)]
+impl Fit {
+    /// A centered fit with the given mode
+    #[inline]
+    pub fn new(mode: FitMode) -> Self {
+        Self { mode, align_x: 0.5, align_y: 0.5 }
+    }
+
+    pub fn with_align(mut self, align_x: f32, align_y: f32) -> Self {
+        self.align_x = align_x;
+        self.align_y = align_y;
+        self
+    }
+
+    /// Where content of the given size ends up, and the part of it that is inside of the rect.
+    pub(crate) fn place(&self, (width, height): (f32, f32), rect: &Rect) -> (Rect, Rect) {
+        let Rect { x: rect_x, y: rect_y, width: rect_width, height: rect_height } = *rect;
+
+        let contain = (rect_width / width).min(rect_height / height);
+        let (dest_width, dest_height) = match self.mode {
+            FitMode::Fill => (rect_width, rect_height),
+            FitMode::Contain => (width * contain, height * contain),
+            FitMode::Cover => {
+                let cover = (rect_width / width).max(rect_height / height);
+                (width * cover, height * cover)
+            },
+            FitMode::None => (width, height),
+            FitMode::ScaleDown => {
+                let scale = contain.min(1.0);
+                (width * scale, height * scale)
+            }
+        };
+
+        let dest_x = rect_x + (rect_width - dest_width) * self.align_x;
+        let dest_y = rect_y + (rect_height - dest_height) * self.align_y;
+
+        let visible_x = dest_x.max(rect_x);
+        let visible_y = dest_y.max(rect_y);
+        let visible_width = ((dest_x + dest_width).min(rect_x + rect_width) - visible_x).max(0.0);
+        let visible_height = ((dest_y + dest_height).min(rect_y + rect_height) - visible_y).max(0.0);
+
+        (
+            Rect { x: dest_x, y: dest_y, width: dest_width, height: dest_height },
+            Rect { x: visible_x, y: visible_y, width: visible_width, height: visible_height }
+        )
+    }
+}
+
+impl Default for Fit {
+    fn default() -> Self {
+        Self::new(FitMode::default())
+    }
+}
+
+/// Distances from the edges of an image in pixels, which split it into the nine patches of an `Object::NinePatch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Insets {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32
+}
+
+impl Insets {
+    #[inline]
+    pub fn new(left: u32, top: u32, right: u32, bottom: u32) -> Self {
+        Self { left, top, right, bottom }
+    }
+
+    #[inline]
+    pub fn new_uniform(inset: u32) -> Self {
+        Self::new(inset, inset, inset, inset)
+    }
+
+    /// The source and destination rect of every non-empty patch.
+    /// If the rect is too small for the corners, they are shrunk proportionally, like Skia's lattices.
+    pub(crate) fn patches(&self, image: Dimensions<u32>, rect: &Rect, scale: f32) -> Vec<(Rect, Rect)> {
+        let columns = Self::divide(image.width, self.left, self.right, rect.x, rect.width, scale);
+        let rows = Self::divide(image.height, self.top, self.bottom, rect.y, rect.height, scale);
+
+        rows.iter()
+        .flat_map(|&(src_y, src_height, dst_y, dst_height)| columns.iter().map(move |&(src_x, src_width, dst_x, dst_width)| (
+            Rect { x: src_x, y: src_y, width: src_width, height: src_height },
+            Rect { x: dst_x, y: dst_y, width: dst_width, height: dst_height }
+        )))
+        .filter(|(source, dest)| !source.is_empty() && !dest.is_empty())
+        .collect()
+    }
+
+    /// Splits one axis into its start, center and end as (source start, source length, destination start, destination length)
+    fn divide(length: u32, start: u32, end: u32, dst_start: f32, dst_length: f32, scale: f32) -> [(f32, f32, f32, f32); 3] {
+        let start = start.min(length);
+        let end = end.min(length - start);
+        let center = length - start - end;
+
+        let fixed = (start + end) as f32 * scale;
+        let (dst_fixed_start, dst_fixed_end) = if fixed > dst_length && fixed > 0.0 {
+            let shrink = dst_length / fixed;
+            (start as f32 * scale * shrink, end as f32 * scale * shrink)
+        } else {
+            (start as f32 * scale, end as f32 * scale)
+        };
+        let dst_center = dst_length - dst_fixed_start - dst_fixed_end;
+
+        [
+            (0.0, start as f32, dst_start, dst_fixed_start),
+            (start as f32, center as f32, dst_start + dst_fixed_start, dst_center),
+            ((start + center) as f32, end as f32, dst_start + dst_fixed_start + dst_center, dst_fixed_end)
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rect {
-    pub x: i32,
-    pub y: i32,
-    pub width: u32,
-    pub height: u32
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32
+}
+
+impl Rect {
+    /// Whether the rect covers no area
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0.0 || self.height <= 0.0
+    }
+
+    /// The smallest rect containing both rects. Empty rects are ignored.
+    pub fn union(&self, other: &Rect) -> Rect {
+        if other.is_empty() { return *self }
+        if self.is_empty() { return *other }
+
+        let (x, y) = (self.x.min(other.x), self.y.min(other.y));
+        Rect {
+            x, y,
+            width: (self.x + self.width).max(other.x + other.width) - x,
+            height: (self.y + self.height).max(other.y + other.height) - y
+        }
+    }
+
+    /// The part that is inside of both rects, which is empty if they don't overlap
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let (x, y) = (self.x.max(other.x), self.y.max(other.y));
+        Rect {
+            x, y,
+            width: ((self.x + self.width).min(other.x + other.width) - x).max(0.0),
+            height: ((self.y + self.height).min(other.y + other.height) - y).max(0.0)
+        }
+    }
+
+    #[inline]
+    pub fn intersects(&self, other: &Rect) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    /// Grows the rect by `amount` in every direction
+    #[inline]
+    pub fn outset(&self, amount: f32) -> Rect {
+        Rect { x: self.x - amount, y: self.y - amount, width: self.width + amount * 2.0, height: self.height + amount * 2.0 }
+    }
+
+    /// The smallest rect containing all four corners after transforming them
+    pub fn transformed(&self, transform: &Transform) -> Rect {
+        let corners = [
+            (self.x, self.y), (self.x + self.width, self.y),
+            (self.x, self.y + self.height), (self.x + self.width, self.y + self.height)
+        ].map(|(x, y)| transform.map_point(x, y));
+
+        let (min_x, min_y) = corners.iter().fold((f32::INFINITY, f32::INFINITY), |(x, y), corner| (x.min(corner.0), y.min(corner.1)));
+        let (max_x, max_y) = corners.iter().fold((f32::NEG_INFINITY, f32::NEG_INFINITY), |(x, y), corner| (x.max(corner.0), y.max(corner.1)));
+
+        Rect { x: min_x, y: min_y, width: max_x - min_x, height: max_y - min_y }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rounding {
-    pub top_l: u16,
-    pub top_r: u16,
-    pub bottom_l: u16,
-    pub bottom_r: u16,
+    pub top_l: f32,
+    pub top_r: f32,
+    pub bottom_l: f32,
+    pub bottom_r: f32,
 }
 
 impl Rounding {
     #[inline]
-    pub fn new(top_l: u16, top_r: u16, bottom_l: u16, bottom_r: u16) -> Self {
+    pub fn new(top_l: f32, top_r: f32, bottom_l: f32, bottom_r: f32) -> Self {
         Self { top_l, top_r, bottom_l, bottom_r }
     }
 
     #[inline]
-    pub fn new_uniform(radius: u16) -> Self {
+    pub fn new_uniform(radius: f32) -> Self {
         Self::new(radius, radius, radius, radius)
     }
 
     #[inline]
-    pub fn new_horizontal(top: u16, bottom: u16) -> Self {
+    pub fn new_horizontal(top: f32, bottom: f32) -> Self {
         Self::new(top, top, bottom, bottom)
     }
 
     #[inline]
-    pub fn new_vertical(left: u16, right: u16) -> Self {
+    pub fn new_vertical(left: f32, right: f32) -> Self {
         Self::new(left, right, left, right)
     }
 }
@@ -54,44 +358,224 @@ impl Rounding {
 #[allow(clippy::too_many_arguments)]
 impl Object {
     #[inline]
-    pub fn rect(x: i32, y: i32, width: u32, height: u32) -> Rect {
+    pub fn rect(x: f32, y: f32, width: f32, height: f32) -> Rect {
         Rect { x, y, width, height }
     }
 
     /// Shorthand function for creating an `Objects::Rectangle` with the given properties.
     #[inline]
-    pub fn rectangle(x: i32, y: i32, width: u32, height: u32, color: u32, rounding: Option<Rounding>) -> Object {
-        Object::Rectangle { color, rounding, rect: Self::rect(x, y, width, height) }
+    pub fn rectangle(x: f32, y: f32, width: f32, height: f32, brush: impl Into<Brush>, rounding: Option<Rounding>) -> Object {
+        Object::Rectangle { brush: brush.into(), rounding, rect: Self::rect(x, y, width, height), stroke: None, shadow: None }
     }
 
     /// Shorthand function for creating an `Objects::Text` with the given properties.
     #[inline]
-    pub fn text(x: i32, y: i32, text: String, font: Option<String>, size: f32, color: u32) -> Object {
-        Object::Text { text, font, color, size, position: Position::new(x, y) }
+    pub fn text(x: f32, y: f32, text: String, font: Option<String>, size: f32, brush: impl Into<Brush>) -> Object {
+        Object::Text { text, font, brush: brush.into(), size, position: Position::new(x, y), decoration: None }
     }
 
     /// Shorthand function for creating an `Objects::Paragraph` with the given properties.
     #[inline]
-    pub fn paragraph(x: i32, y: i32, paragraph: Paragraph) -> Object {
+    pub fn paragraph(x: f32, y: f32, paragraph: Paragraph) -> Object {
         Object::Paragraph { position: Position::new(x, y), paragraph }
     }
 
     /// Shorthand function for creating an `Objects::Image` with the given properties.
     #[inline]
-    pub fn image(x: i32, y: i32, width: u32, height: u32, image: CacheableImage) -> Object {
-        Object::Image { image, rect: Self::rect(x, y, width, height) }
+    pub fn image(x: f32, y: f32, width: f32, height: f32, image: CacheableImage) -> Object {
+        Object::Image { image, rect: Self::rect(x, y, width, height), source: None, fit: Fit::default() }
     }
     
+    /// Shorthand function for creating an `Objects::Image` showing the frame of the animation at `elapsed`.
+    #[inline]
+    pub fn animated_image(x: f32, y: f32, width: f32, height: f32, image: &AnimatedImage, elapsed: Duration) -> Object {
+        Self::image(x, y, width, height, image.frame_at(elapsed).clone())
+    }
+
+    /// Shorthand function for creating an `Objects::NinePatch` with the given properties, drawing the corners at their original size.
+    #[inline]
+    pub fn nine_patch(x: f32, y: f32, width: f32, height: f32, image: CacheableImage, insets: Insets) -> Object {
+        Object::NinePatch { image, rect: Self::rect(x, y, width, height), insets, scale: 1.0 }
+    }
+
     /// Shorthand function for creating an `Objects::Svg` with the given properties.  
     #[inline]
-    pub fn svg(x: i32, y: i32, width: u32, height: u32, svg: CacheableSvg, color: u32) -> Object {
-        Object::Svg { svg, color, rect: Self::rect(x, y, width, height) }
+    pub fn svg(x: f32, y: f32, width: f32, height: f32, svg: CacheableSvg, brush: impl Into<Brush>) -> Object {
+        Object::Svg { svg, brush: brush.into(), rect: Self::rect(x, y, width, height), fit: Fit::default() }
+    }
+
+    /// Shorthand function for creating an `Objects::Path` with the given properties.
+    #[inline]
+    pub fn path(path: Path, brush: impl Into<Brush>) -> Object {
+        Object::Path { path, brush: brush.into(), stroke: None }
+    }
+
+    /// Shorthand function for creating a circle around the given center point.
+    #[inline]
+    pub fn circle(cx: f32, cy: f32, radius: f32, brush: impl Into<Brush>) -> Object {
+        Self::path(Path::circle(cx, cy, radius), brush)
+    }
+
+    /// Shorthand function for creating an ellipse around the given center point.
+    #[inline]
+    pub fn ellipse(cx: f32, cy: f32, rx: f32, ry: f32, brush: impl Into<Brush>) -> Object {
+        Self::path(Path::ellipse(cx, cy, rx, ry), brush)
+    }
+
+    /// Shorthand function for creating a pie slice, with angles in radians (see `Path`).
+    #[inline]
+    pub fn pie(cx: f32, cy: f32, radius: f32, start_angle: f32, sweep_angle: f32, brush: impl Into<Brush>) -> Object {
+        Self::path(Path::pie(cx, cy, radius, start_angle, sweep_angle), brush)
+    }
+
+    /// Shorthand function for creating a closed polygon through the given points.
+    #[inline]
+    pub fn polygon(points: &[(f32, f32)], brush: impl Into<Brush>) -> Object {
+        Self::path(Path::polygon(points), brush)
+    }
+
+    /// Shorthand function for creating a stroked line between two points.
+    #[inline]
+    pub fn line(x1: f32, y1: f32, x2: f32, y2: f32, stroke: Stroke) -> Object {
+        Object::Path { path: Path::new().move_to(x1, y1).line_to(x2, y2), brush: Brush::Solid(0), stroke: Some(stroke) }
+    }
+
+    /// Shorthand function for creating an `Objects::Group` with the given properties.
+    #[inline]
+    pub fn group(transform: Transform, children: Vec<Object>) -> Object {
+        Object::Group { transform, children }
+    }
+
+    /// Shorthand function for creating an `Objects::Clip` with the given properties.
+    #[inline]
+    pub fn clip(shape: ClipShape, children: Vec<Object>) -> Object {
+        Object::Clip { shape, children }
+    }
+
+    /// Shorthand function for creating an `Objects::Layer` with the given properties.
+    #[inline]
+    pub fn layer(opacity: f32, blend: BlendMode, children: Vec<Object>) -> Object {
+        Object::Layer { opacity, blend, children }
+    }
+
+    /// Shorthand function for creating an `Objects::BackdropBlur` with the given properties.
+    #[inline]
+    pub fn backdrop_blur(x: f32, y: f32, width: f32, height: f32, blur: f32, tint: Option<Brush>, rounding: Option<Rounding>) -> Object {
+        Object::BackdropBlur { rect: Self::rect(x, y, width, height), rounding, blur, tint }
+    }
+
+    /// Sets the stroke of an `Objects::Rectangle` or `Objects::Path`, other objects are returned unchanged.  
+    /// The shape is still filled with its brush, so use a transparent color to only draw the outline.
+    pub fn with_stroke(self, stroke: Stroke) -> Self {
+        match self {
+            Object::Rectangle { rounding, brush, rect, shadow, .. } => Object::Rectangle { rounding, brush, rect, stroke: Some(stroke), shadow },
+            Object::Path { path, brush, .. } => Object::Path { path, brush, stroke: Some(stroke) },
+            other => other
+        }
+    }
+
+    /// Underlines, strikes through or overlines an `Objects::Text`, other objects are returned unchanged.
+    /// Paragraphs get their decorations from their `TextOptions` or spans instead.
+    pub fn with_decoration(self, decoration: TextDecoration) -> Self {
+        match self {
+            Object::Text { text, font, size, brush, position, .. } => Object::Text { text, font, size, brush, position, decoration: Some(decoration) },
+            other => other
+        }
+    }
+
+    /// Sets the shadow of an `Objects::Rectangle`, other objects are returned unchanged.
+    pub fn with_shadow(self, shadow: Shadow) -> Self {
+        match self {
+            Object::Rectangle { rounding, brush, rect, stroke, .. } => Object::Rectangle { rounding, brush, rect, stroke, shadow: Some(shadow) },
+            other => other
+        }
+    }
+
+    /// Sets how an `Objects::Image` or `Objects::Svg` is fitted into its rect, other objects are returned unchanged.
+    pub fn with_fit(self, fit: Fit) -> Self {
+        match self {
+            Object::Image { image, rect, source, .. } => Object::Image { image, rect, source, fit },
+            Object::Svg { svg, brush, rect, .. } => Object::Svg { svg, brush, rect, fit },
+            other => other
+        }
+    }
+
+    /// Only draws the given part of an `Objects::Image`, in image pixels, like a sprite from a sprite sheet.
+    /// Other objects are returned unchanged.
+    pub fn with_source(self, source: Rect) -> Self {
+        match self {
+            Object::Image { image, rect, fit, .. } => Object::Image { image, rect, source: Some(source), fit },
+            other => other
+        }
+    }
+
+    /// Gives the object an id, which `RendererData::hit_test` returns when the object is hit.
+    #[inline]
+    pub fn with_id(self, id: u64) -> Self {
+        Object::Tagged { id, object: Box::new(self) }
     }
 
     #[inline]
     pub fn scale_with(self, window: &Window) -> Self {
         self * window.current_scale()
     }
+
+    /// The area the object can draw to, including strokes, shadows and antialiasing.
+    /// Returns `None` if that isn't known without laying out text, like for `Object::Text`.
+    #[inline]
+    pub fn bounds(&self) -> Option<Rect> {
+        self.bounds_with(&|_, _, _| None)
+    }
+
+    /// Like `bounds`, measuring `Object::Text` as (width, height) from its text, font and size.
+    pub(crate) fn bounds_with(&self, measure_text: MeasureText) -> Option<Rect> {
+        let bounds = match self {
+            Object::Rectangle { rounding, rect, stroke, shadow, .. } => {
+                let bounds = rect.outset(stroke.as_ref().map_or(0.0, Stroke::outset));
+
+                match shadow {
+                    Some(shadow) => {
+                        let ((x, y, width, height), _) = shadow.shape(rect, rounding.as_ref());
+                        // Three standard deviations, where the blur fades out
+                        bounds.union(&Rect { x, y, width, height }.outset(shadow.blur * 1.5))
+                    },
+                    None => bounds
+                }
+            },
+            // Glyphs can reach below their line, like descenders, or past their advance, like italics
+            Object::Text { text, font, size, position, .. } => {
+                let (width, height) = measure_text(text, font, *size)?;
+                Rect { x: position.x, y: position.y, width, height }.outset(size / 2.0)
+            },
+            Object::Paragraph { paragraph, position } => {
+                // Unwrapped lines can be longer than the paragraph
+                if paragraph.options().wrap == TextWrap::NoWrap { return None }
+
+                let size = paragraph.spans().iter().map(|span| span.style.size).fold(paragraph.options().size, f32::max);
+                Rect { x: position.x, y: position.y, width: paragraph.width(), height: paragraph.height() }.outset(size / 2.0)
+            },
+            Object::Image { rect, .. } | Object::NinePatch { rect, .. } | Object::Svg { rect, .. } | Object::BackdropBlur { rect, .. } => *rect,
+            Object::Path { path, stroke, .. } => path.control_bounds().outset(stroke.as_ref().map_or(0.0, Stroke::outset)),
+            Object::Group { transform, children } => Self::children_bounds(children, measure_text)?.transformed(transform),
+            Object::Clip { shape, children } => {
+                let clip = match shape {
+                    ClipShape::Rect(rect) | ClipShape::RoundedRect(rect, _) => *rect,
+                    ClipShape::Path(path) => path.control_bounds()
+                };
+
+                Self::children_bounds(children, measure_text)?.intersection(&clip)
+            },
+            Object::Layer { children, .. } => Self::children_bounds(children, measure_text)?,
+            Object::Tagged { object, .. } => return object.bounds_with(measure_text)
+        };
+
+        // Antialiasing can touch the pixels around the shape
+        Some(bounds.outset(1.0))
+    }
+
+    fn children_bounds(children: &[Object], measure_text: MeasureText) -> Option<Rect> {
+        children.iter().try_fold(Rect::default(), |bounds, child| Some(bounds.union(&child.bounds_with(measure_text)?)))
+    }
 }
 
 impl Mul<f32> for Object {
@@ -101,25 +585,84 @@ impl Mul<f32> for Object {
         if with == 1.0 { return self; }
 
         match self {
-            Object::Rectangle { rounding, color, rect } => Object::Rectangle {
-                rounding, color, rect: rect * with
+            Object::Rectangle { rounding, brush, rect, stroke, shadow } => Object::Rectangle {
+                rounding: rounding.map(|rounding| rounding * with),
+                brush: brush * with,
+                rect: rect * with,
+                stroke: stroke.map(|stroke| stroke * with),
+                shadow: shadow.map(|shadow| shadow * with)
             },
-            Object::Text { text, font, size, color, position } => Object::Text {
-                text, font, size: size * with, color, position: position * with
+            Object::Text { text, font, size, brush, position, decoration } => Object::Text {
+                text, font, size: size * with, brush: brush * with, position: scale_position(position, with),
+                decoration: decoration.map(|decoration| decoration * with)
             },
             Object::Paragraph { position, paragraph } => Object::Paragraph {
-                position: position * with, paragraph
+                position: scale_position(position, with), paragraph
             },
-            Object::Image { rect, image } => Object::Image {
-                rect: rect * with, image
+            Object::Image { rect, image, source, fit } => Object::Image {
+                rect: rect * with, image, source, fit
             },
-            Object::Svg { rect, svg, color } => Object::Svg {
-                rect: rect * with, svg, color
-            }
+            Object::NinePatch { image, rect, insets, scale } => Object::NinePatch {
+                image, rect: rect * with, insets, scale: scale * with
+            },
+            Object::Svg { rect, svg, brush, fit } => Object::Svg {
+                rect: rect * with, svg, brush: brush * with, fit
+            },
+            Object::Path { path, brush, stroke } => Object::Path {
+                path: path * with, brush: brush * with, stroke: stroke.map(|stroke| stroke * with)
+            },
+            // The children are scaled, so only the translation has to be
+            Object::Group { transform, children } => Object::Group {
+                transform: Transform { tx: transform.tx * with, ty: transform.ty * with, ..transform },
+                children: children.into_iter().map(|child| child * with).collect()
+            },
+            Object::Clip { shape, children } => Object::Clip {
+                shape: shape * with,
+                children: children.into_iter().map(|child| child * with).collect()
+            },
+            Object::Layer { opacity, blend, children } => Object::Layer {
+                opacity,
+                blend,
+                children: children.into_iter().map(|child| child * with).collect()
+            },
+            Object::BackdropBlur { rect, rounding, blur, tint } => Object::BackdropBlur {
+                rect: rect * with,
+                rounding: rounding.map(|rounding| rounding * with),
+                blur: blur * with,
+                tint: tint.map(|tint| tint * with)
+            },
+            Object::Tagged { id, object } => Object::Tagged { id, object: Box::new(*object * with) }
+        }
+    }
+}
+
+/// `Position`'s `Mul` rounds to whole pixels, which objects don't want
+#[inline]
+fn scale_position(position: Position<f32>, with: f32) -> Position<f32> {
+    Position::new(position.x * with, position.y * with)
+}
+
+impl Mul<f32> for ClipShape {
+    type Output = Self;
+
+    fn mul(self, with: f32) -> Self::Output {
+        match self {
+            ClipShape::Rect(rect) => ClipShape::Rect(rect * with),
+            ClipShape::RoundedRect(rect, rounding) => ClipShape::RoundedRect(rect * with, rounding * with),
+            ClipShape::Path(path) => ClipShape::Path(path * with)
         }
     }
 }
 
+impl Mul<f32> for Rounding {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, with: f32) -> Self::Output {
+        Self::new(self.top_l * with, self.top_r * with, self.bottom_l * with, self.bottom_r * with)
+    }
+}
+
 impl Mul<f32> for Rect {
     type Output = Self;
 
@@ -127,11 +670,92 @@ impl Mul<f32> for Rect {
     fn mul(self, with: f32) -> Self::Output {
         let Self { x, y, width, height } = self;
 
-        let x = (x as f32 * with).round() as i32;
-        let y = (y as f32 * with).round() as i32;
-        let width = (width as f32 * with).round() as u32;
-        let height = (height as f32 * with).round() as u32;
+        Self { x: x * with, y: y * with, width: width * with, height: height * with }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structs::Dimensions;
 
-        Self { x, y, width, height }
+    use super::{Fit, FitMode, Insets, Object, Rect};
+
+    /// A rect of 100x100 at (10, 10)
+    fn place(fit: Fit, size: (f32, f32)) -> (Rect, Rect) {
+        fit.place(size, &Object::rect(10.0, 10.0, 100.0, 100.0))
+    }
+
+    #[test]
+    fn fill_stretches() {
+        let (dest, visible) = place(Fit::new(FitMode::Fill), (200.0, 50.0));
+
+        assert_eq!(dest, Object::rect(10.0, 10.0, 100.0, 100.0));
+        assert_eq!(visible, dest);
+    }
+
+    #[test]
+    fn contain_keeps_aspect_ratio() {
+        let (dest, visible) = place(Fit::new(FitMode::Contain), (200.0, 100.0));
+
+        assert_eq!(dest, Object::rect(10.0, 35.0, 100.0, 50.0));
+        assert_eq!(visible, dest);
+    }
+
+    #[test]
+    fn cover_cuts_off_the_sides() {
+        let (dest, visible) = place(Fit::new(FitMode::Cover), (200.0, 100.0));
+
+        assert_eq!(dest, Object::rect(-40.0, 10.0, 200.0, 100.0));
+        assert_eq!(visible, Object::rect(10.0, 10.0, 100.0, 100.0));
+    }
+
+    #[test]
+    fn none_keeps_size() {
+        let (dest, visible) = place(Fit::new(FitMode::None), (50.0, 300.0));
+
+        assert_eq!(dest, Object::rect(35.0, -90.0, 50.0, 300.0));
+        assert_eq!(visible, Object::rect(35.0, 10.0, 50.0, 100.0));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn scale_down_only_shrinks() {
+        let (small, _) = place(Fit::new(FitMode::ScaleDown), (50.0, 20.0));
+        let (large, _) = place(Fit::new(FitMode::ScaleDown), (400.0, 200.0));
+
+        assert_eq!(small, Object::rect(35.0, 50.0, 50.0, 20.0));
+        assert_eq!(large, Object::rect(10.0, 35.0, 100.0, 50.0));
+    }
+
+    #[test]
+    fn align_moves_content() {
+        let (dest, _) = place(Fit::new(FitMode::Contain).with_align(1.0, 0.0), (100.0, 200.0));
+
+        assert_eq!(dest, Object::rect(60.0, 10.0, 50.0, 100.0));
+    }
+
+    #[test]
+    fn patches_of_image_smaller_than_insets() {
+        // The right and bottom insets are cut down to what is left of the image, which has no center
+        let patches = Insets::new_uniform(8).patches(Dimensions::new(10, 10), &Object::rect(0.0, 0.0, 100.0, 100.0), 1.0);
+
+        assert_eq!(patches, vec![
+            (Object::rect(0.0, 0.0, 8.0, 8.0), Object::rect(0.0, 0.0, 8.0, 8.0)),
+            (Object::rect(8.0, 0.0, 2.0, 8.0), Object::rect(98.0, 0.0, 2.0, 8.0)),
+            (Object::rect(0.0, 8.0, 8.0, 2.0), Object::rect(0.0, 98.0, 8.0, 2.0)),
+            (Object::rect(8.0, 8.0, 2.0, 2.0), Object::rect(98.0, 98.0, 2.0, 2.0))
+        ]);
+    }
+
+    #[test]
+    fn patches_shrink_corners_that_do_not_fit() {
+        let patches = Insets::new_uniform(4).patches(Dimensions::new(12, 12), &Object::rect(10.0, 10.0, 4.0, 4.0), 1.0);
+
+        // The corners only take half of their size, leaving nothing for the edges and the center
+        assert_eq!(patches, vec![
+            (Object::rect(0.0, 0.0, 4.0, 4.0), Object::rect(10.0, 10.0, 2.0, 2.0)),
+            (Object::rect(8.0, 0.0, 4.0, 4.0), Object::rect(12.0, 10.0, 2.0, 2.0)),
+            (Object::rect(0.0, 8.0, 4.0, 4.0), Object::rect(10.0, 12.0, 2.0, 2.0)),
+            (Object::rect(8.0, 8.0, 4.0, 4.0), Object::rect(12.0, 12.0, 2.0, 2.0))
+        ]);
+    }
+}
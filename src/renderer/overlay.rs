@@ -0,0 +1,129 @@
+use std::{cell::{Cell, RefCell}, collections::VecDeque, time::{Duration, Instant}};
+
+use super::{objects::{Object, Rect, Rounding}, strokes::Stroke};
+
+
+/// How many frames the frame time graph shows
+const HISTORY: usize = 120;
+/// How many of the last frames the FPS are averaged over
+const AVERAGED: usize = 30;
+/// The frame time at the top of the graph
+const GRAPH_MAX: Duration = Duration::from_millis(50);
+
+const PANEL: Rect = Rect { x: 8.0, y: 8.0, width: 212.0, height: 112.0 };
+const GRAPH: Rect = Rect { x: 16.0, y: 56.0, width: 196.0, height: 56.0 };
+const TEXT_SIZE: f32 = 13.0;
+
+const PANEL_COLOR: u32 = 0x000000B4;
+const TEXT_COLOR: u32 = 0xFFFFFFFF;
+const GUIDE_COLOR: u32 = 0xFFFFFF40;
+const BOUNDS_COLOR: u32 = 0xFF44FFCC;
+const FAST_COLOR: u32 = 0x66DD77FF;
+const SLOW_COLOR: u32 = 0xFFCC44FF;
+const VERY_SLOW_COLOR: u32 = 0xFF5555FF;
+
+/// Frame times of 60 and 30 FPS, which get a guide line in the graph
+const FAST: Duration = Duration::from_micros(16_667);
+const SLOW: Duration = Duration::from_micros(33_333);
+
+
+/// ### Draws FPS, a frame time graph, the object count and the bounds of every object over a frame.
+/// Every renderer has its own, which is toggled with `RendererTrait::set_debug_overlay`.
+#[derive(Debug)]
+pub(crate) struct DebugOverlay {
+    enabled: Cell<bool>,
+    last_frame: Cell<Option<Instant>>,
+    frame_times: RefCell<VecDeque<Duration>>
+}
+
+impl DebugOverlay {
+    /// Enabled if the `LUMI_DEBUG_OVERLAY` environment variable is set
+    pub(crate) fn new() -> Self {
+        Self {
+            enabled: Cell::new(crate::debug_overlay()),
+            last_frame: Cell::new(None),
+            frame_times: RefCell::new(VecDeque::with_capacity(HISTORY))
+        }
+    }
+
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.enabled.set(enabled);
+        // The time it was disabled would otherwise show up as one long frame
+        self.last_frame.set(None);
+        self.frame_times.borrow_mut().clear();
+    }
+
+    #[inline]
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled.get()
+    }
+
+    /// Records a frame and returns the objects to draw on top of it, which is nothing if the overlay is disabled.
+    /// Text is only drawn if a font is registered, `bounds` gives the logical bounds of an object.
+    pub(crate) fn objects(&self, objects: &[&Object], has_font: bool, bounds: impl Fn(&Object) -> Option<Rect>) -> Vec<Object> {
+        if !self.enabled.get() { return Vec::new() }
+
+        let now = Instant::now();
+        let mut frame_times = self.frame_times.borrow_mut();
+        if let Some(last_frame) = self.last_frame.replace(Some(now)) {
+            if frame_times.len() == HISTORY { frame_times.pop_front(); }
+            frame_times.push_back(now - last_frame);
+        }
+
+        let mut overlay: Vec<Object> = objects.iter()
+        .filter_map(|object| bounds(object))
+        .filter(|bounds| !bounds.is_empty())
+        .map(|bounds| Object::rectangle(bounds.x, bounds.y, bounds.width, bounds.height, 0x00000000, None).with_stroke(Stroke::new(1.0, BOUNDS_COLOR)))
+        .collect();
+
+        overlay.push(Object::rectangle(PANEL.x, PANEL.y, PANEL.width, PANEL.height, PANEL_COLOR, Some(Rounding::new_uniform(6.0))));
+
+        if has_font {
+            let averaged = frame_times.len().min(AVERAGED);
+            let average = frame_times.iter().rev().take(averaged).sum::<Duration>().checked_div(averaged as u32).unwrap_or_default();
+            let fps = if average.is_zero() { 0.0 } else { 1.0 / average.as_secs_f32() };
+
+            overlay.extend([
+                Object::text(GRAPH.x, PANEL.y + 6.0, format!("{fps:.0} FPS ({:.2} ms)", average.as_secs_f32() * 1000.0), None, TEXT_SIZE, TEXT_COLOR),
+                Object::text(GRAPH.x, PANEL.y + 24.0, format!("{} objects", objects.iter().map(|object| count_objects(object)).sum::<usize>()), None, TEXT_SIZE, TEXT_COLOR)
+            ]);
+        }
+
+        let height_of = |time: Duration| (time.as_secs_f32() / GRAPH_MAX.as_secs_f32()).min(1.0) * GRAPH.height;
+
+        for guide in [FAST, SLOW] {
+            overlay.push(Object::rectangle(GRAPH.x, GRAPH.y + GRAPH.height - height_of(guide), GRAPH.width, 1.0, GUIDE_COLOR, None));
+        }
+
+        // The newest frame is on the right
+        let bar_width = GRAPH.width / HISTORY as f32;
+        let first_bar = HISTORY - frame_times.len();
+
+        for (index, &time) in frame_times.iter().enumerate() {
+            let color = match time {
+                time if time <= FAST => FAST_COLOR,
+                time if time <= SLOW => SLOW_COLOR,
+                _ => VERY_SLOW_COLOR
+            };
+            let height = height_of(time);
+
+            overlay.push(Object::rectangle(
+                GRAPH.x + (first_bar + index) as f32 * bar_width, GRAPH.y + GRAPH.height - height, bar_width, height, color, None
+            ));
+        }
+
+        overlay
+    }
+}
+
+/// Counts the object and all of its children
+fn count_objects(object: &Object) -> usize {
+    let children = match object {
+        Object::Group { children, .. } | Object::Clip { children, .. } | Object::Layer { children, .. } => children.iter().map(count_objects).sum(),
+        // Only a wrapper around its object
+        Object::Tagged { object, .. } => return count_objects(object),
+        _ => 0
+    };
+
+    1 + children
+}
@@ -0,0 +1,215 @@
+use std::{f32::consts::{FRAC_PI_2, TAU}, ops::Mul};
+
+use super::objects::Rect;
+
+
+/// A vector path, built from drawing commands.
+/// Angles are in radians, starting at the positive x axis and going clockwise (as y points down).
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Path {
+    pub commands: Vec<PathCommand>,
+    pub fill_rule: FillRule
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PathCommand {
+    MoveTo { x: f32, y: f32 },
+    LineTo { x: f32, y: f32 },
+    /// Quadratic bezier curve with one control point
+    QuadTo { cx: f32, cy: f32, x: f32, y: f32 },
+    /// Cubic bezier curve with two control points
+    CubicTo { cx1: f32, cy1: f32, cx2: f32, cy2: f32, x: f32, y: f32 },
+    /// Elliptical arc around a center point.
+    /// If the path already has a current point, a line is drawn to the start of the arc first.
+    Arc { cx: f32, cy: f32, rx: f32, ry: f32, start_angle: f32, sweep_angle: f32 },
+    Close
+}
+
+/// Decides which parts of self-intersecting or nested paths are filled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FillRule {
+    #[default]
+    NonZero,
+    EvenOdd
+}
+
+/// A path command that every renderer can draw natively, with arcs already converted to cubic curves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum PathElement {
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    QuadTo(f32, f32, f32, f32),
+    CubicTo(f32, f32, f32, f32, f32, f32),
+    Close
+}
+
+impl Path {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn move_to(mut self, x: f32, y: f32) -> Self {
+        self.commands.push(PathCommand::MoveTo { x, y });
+        self
+    }
+
+    pub fn line_to(mut self, x: f32, y: f32) -> Self {
+        self.commands.push(PathCommand::LineTo { x, y });
+        self
+    }
+
+    pub fn quad_to(mut self, cx: f32, cy: f32, x: f32, y: f32) -> Self {
+        self.commands.push(PathCommand::QuadTo { cx, cy, x, y });
+        self
+    }
+
+    pub fn cubic_to(mut self, cx1: f32, cy1: f32, cx2: f32, cy2: f32, x: f32, y: f32) -> Self {
+        self.commands.push(PathCommand::CubicTo { cx1, cy1, cx2, cy2, x, y });
+        self
+    }
+
+    pub fn arc(mut self, cx: f32, cy: f32, rx: f32, ry: f32, start_angle: f32, sweep_angle: f32) -> Self {
+        self.commands.push(PathCommand::Arc { cx, cy, rx, ry, start_angle, sweep_angle });
+        self
+    }
+
+    pub fn close(mut self) -> Self {
+        self.commands.push(PathCommand::Close);
+        self
+    }
+
+    pub fn with_fill_rule(mut self, fill_rule: FillRule) -> Self {
+        self.fill_rule = fill_rule;
+        self
+    }
+
+    /// An ellipse around the given center point
+    pub fn ellipse(cx: f32, cy: f32, rx: f32, ry: f32) -> Self {
+        Self::new()
+        .arc(cx, cy, rx, ry, 0.0, TAU)
+        .close()
+    }
+
+    /// A circle around the given center point
+    #[inline]
+    pub fn circle(cx: f32, cy: f32, radius: f32) -> Self {
+        Self::ellipse(cx, cy, radius, radius)
+    }
+
+    /// A pie slice, going from the center along the arc and back
+    pub fn pie(cx: f32, cy: f32, radius: f32, start_angle: f32, sweep_angle: f32) -> Self {
+        Self::new()
+        .move_to(cx, cy)
+        .arc(cx, cy, radius, radius, start_angle, sweep_angle)
+        .close()
+    }
+
+    /// A closed polygon through all given points
+    pub fn polygon(points: &[(f32, f32)]) -> Self {
+        let mut path = Self::new();
+
+        for (index, &(x, y)) in points.iter().enumerate() {
+            path = if index == 0 { path.move_to(x, y) } else { path.line_to(x, y) };
+        }
+
+        path.close()
+    }
+
+    /// A rect containing every point of the path, including control points, so the curves are always inside of it.
+    pub(crate) fn control_bounds(&self) -> Rect {
+        let points = self.elements().into_iter().flat_map(|element| match element {
+            PathElement::MoveTo(x, y) | PathElement::LineTo(x, y) => vec![(x, y)],
+            PathElement::QuadTo(cx, cy, x, y) => vec![(cx, cy), (x, y)],
+            PathElement::CubicTo(cx1, cy1, cx2, cy2, x, y) => vec![(cx1, cy1), (cx2, cy2), (x, y)],
+            PathElement::Close => Vec::new()
+        });
+
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f32::INFINITY, f32::INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for (x, y) in points {
+            (min_x, min_y, max_x, max_y) = (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y));
+        }
+
+        if min_x > max_x { return Rect::default() }
+        Rect { x: min_x, y: min_y, width: max_x - min_x, height: max_y - min_y }
+    }
+
+    /// Resolves the commands into elements that can be passed to a renderer.
+    pub(crate) fn elements(&self) -> Vec<PathElement> {
+        let mut elements = Vec::with_capacity(self.commands.len());
+        let mut has_current = false;
+
+        for command in &self.commands {
+            match *command {
+                PathCommand::MoveTo { x, y } => elements.push(PathElement::MoveTo(x, y)),
+                PathCommand::LineTo { x, y } => elements.push(PathElement::LineTo(x, y)),
+                PathCommand::QuadTo { cx, cy, x, y } => elements.push(PathElement::QuadTo(cx, cy, x, y)),
+                PathCommand::CubicTo { cx1, cy1, cx2, cy2, x, y } => elements.push(PathElement::CubicTo(cx1, cy1, cx2, cy2, x, y)),
+                PathCommand::Arc { cx, cy, rx, ry, start_angle, sweep_angle } => {
+                    let start = (cx + rx * start_angle.cos(), cy + ry * start_angle.sin());
+                    elements.push(if has_current {
+                        PathElement::LineTo(start.0, start.1)
+                    } else {
+                        PathElement::MoveTo(start.0, start.1)
+                    });
+
+                    append_arc(&mut elements, cx, cy, rx, ry, start_angle, sweep_angle);
+                },
+                PathCommand::Close => elements.push(PathElement::Close)
+            }
+
+            has_current = !matches!(command, PathCommand::Close);
+        }
+
+        elements
+    }
+}
+
+/// Approximates the arc with one cubic bezier curve per quarter turn (or less).
+fn append_arc(elements: &mut Vec<PathElement>, cx: f32, cy: f32, rx: f32, ry: f32, start_angle: f32, sweep_angle: f32) {
+    let sweep_angle = sweep_angle.clamp(-TAU, TAU);
+    let segments = (sweep_angle.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+    let step = sweep_angle / segments as f32;
+    // Distance of the control points along the tangent, relative to the radius
+    let k = 4.0 / 3.0 * (step / 4.0).tan();
+
+    let mut angle = start_angle;
+    for _ in 0..segments {
+        let (sin_a, cos_a) = angle.sin_cos();
+        let (sin_b, cos_b) = (angle + step).sin_cos();
+
+        elements.push(PathElement::CubicTo(
+            cx + rx * (cos_a - k * sin_a), cy + ry * (sin_a + k * cos_a),
+            cx + rx * (cos_b + k * sin_b), cy + ry * (sin_b - k * cos_b),
+            cx + rx * cos_b, cy + ry * sin_b
+        ));
+
+        angle += step;
+    }
+}
+
+impl Mul<f32> for Path {
+    type Output = Self;
+
+    fn mul(self, with: f32) -> Self::Output {
+        let commands = self.commands.into_iter().map(|command| match command {
+            PathCommand::MoveTo { x, y } => PathCommand::MoveTo { x: x * with, y: y * with },
+            PathCommand::LineTo { x, y } => PathCommand::LineTo { x: x * with, y: y * with },
+            PathCommand::QuadTo { cx, cy, x, y } => PathCommand::QuadTo {
+                cx: cx * with, cy: cy * with, x: x * with, y: y * with
+            },
+            PathCommand::CubicTo { cx1, cy1, cx2, cy2, x, y } => PathCommand::CubicTo {
+                cx1: cx1 * with, cy1: cy1 * with, cx2: cx2 * with, cy2: cy2 * with, x: x * with, y: y * with
+            },
+            PathCommand::Arc { cx, cy, rx, ry, start_angle, sweep_angle } => PathCommand::Arc {
+                cx: cx * with, cy: cy * with, rx: rx * with, ry: ry * with, start_angle, sweep_angle
+            },
+            PathCommand::Close => PathCommand::Close
+        }).collect();
+
+        Self { commands, fill_rule: self.fill_rule }
+    }
+}
@@ -0,0 +1,308 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::structs::Dimensions;
+
+use super::objects::{Object, Rect};
+
+
+/// Identifies an object in a `RetainedScene`. Stays the same when the object is updated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectId(u64);
+
+/// The parts of a window that have to be redrawn, in physical pixels
+#[derive(Debug, Clone, PartialEq)]
+pub enum Damage {
+    None,
+    Full,
+    Regions(Vec<Rect>)
+}
+
+/// ### A list of objects that is kept between frames.
+/// Objects are added once and then updated through their `ObjectId`.
+/// When rendered with `RendererTrait::render_scene`, the scene is compared to the last drawn frame,
+/// so frames without changes are skipped and, where the renderer supports it, only the damaged regions are redrawn.
+#[derive(Debug, Default)]
+pub struct RetainedScene {
+    objects: Vec<(ObjectId, Object)>,
+    next_id: u64,
+    drawn: Option<DrawnFrame>
+}
+
+/// What the scene looked like when it was last drawn
+#[derive(Debug)]
+struct DrawnFrame {
+    objects: Vec<(ObjectId, Object)>,
+    dimensions: Dimensions<u32>,
+    scale: f32,
+    clear_color: u32
+}
+
+/// More regions than this are merged into one, as redrawing every object for each of them costs more than it saves
+const MAX_REGIONS: usize = 16;
+
+impl RetainedScene {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the object on top of all others
+    pub fn insert(&mut self, object: Object) -> ObjectId {
+        let id = ObjectId(self.next_id);
+        self.next_id += 1;
+        self.objects.push((id, object));
+        id
+    }
+
+    /// Replaces the object, keeping its place. Returns the previous object, or `None` if there is no object with this id.
+    pub fn update(&mut self, id: ObjectId, object: Object) -> Option<Object> {
+        self.get_mut(id).map(|current| std::mem::replace(current, object))
+    }
+
+    pub fn remove(&mut self, id: ObjectId) -> Option<Object> {
+        let index = self.objects.iter().position(|(object_id, _)| *object_id == id)?;
+        Some(self.objects.remove(index).1)
+    }
+
+    pub fn get(&self, id: ObjectId) -> Option<&Object> {
+        self.objects.iter().find(|(object_id, _)| *object_id == id).map(|(_, object)| object)
+    }
+
+    pub fn get_mut(&mut self, id: ObjectId) -> Option<&mut Object> {
+        self.objects.iter_mut().find(|(object_id, _)| *object_id == id).map(|(_, object)| object)
+    }
+
+    /// All objects with their ids, from bottom to top
+    pub fn iter(&self) -> impl Iterator<Item = (ObjectId, &Object)> {
+        self.objects.iter().map(|(id, object)| (*id, object))
+    }
+
+    /// All objects from bottom to top, as expected by `RendererTrait::render`
+    pub fn objects(&self) -> Vec<&Object> {
+        self.objects.iter().map(|(_, object)| object).collect()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Removes all objects. Ids are never reused.
+    pub fn clear(&mut self) {
+        self.objects.clear();
+    }
+
+    /// Forgets the last drawn frame, so the next one is drawn in full.
+    /// Needed when the window's content got lost, for example when switching renderers.
+    pub fn invalidate(&mut self) {
+        self.drawn = None;
+    }
+
+    /// Compares the scene to the last drawn frame. `bounds` gives the logical bounds of an object, or `None` if they are unknown.
+    pub(crate) fn damage(&self, dimensions: Dimensions<u32>, scale: f32, clear_color: u32, bounds: impl Fn(&Object) -> Option<Rect>) -> Damage {
+        let Some(drawn) = &self.drawn else { return Damage::Full };
+        if drawn.dimensions != dimensions || drawn.scale != scale || drawn.clear_color != clear_color { return Damage::Full }
+
+        let previous: HashMap<ObjectId, &Object> = drawn.objects.iter().map(|(id, object)| (*id, object)).collect();
+        let current: HashSet<ObjectId> = self.objects.iter().map(|(id, _)| *id).collect();
+        let mut changed = Vec::new();
+
+        for (id, object) in &self.objects {
+            match previous.get(id) {
+                Some(&old) if old == object => {},
+                Some(&old) => changed.extend([old, object]),
+                None => changed.push(object)
+            }
+        }
+        changed.extend(drawn.objects.iter().filter(|(id, _)| !current.contains(id)).map(|(_, object)| object));
+
+        // Unchanged objects can still have been moved above or below each other
+        let unchanged_order = |objects: &[(ObjectId, Object)], other: &[(ObjectId, Object)]| {
+            let other: HashMap<ObjectId, &Object> = other.iter().map(|(id, object)| (*id, object)).collect();
+            objects.iter()
+            .filter(|(id, object)| other.get(id).is_some_and(|&other| other == object))
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>()
+        };
+        if unchanged_order(&drawn.objects, &self.objects) != unchanged_order(&self.objects, &drawn.objects) { return Damage::Full }
+        if changed.is_empty() { return Damage::None }
+
+        let window = Rect { x: 0.0, y: 0.0, width: dimensions.width as f32, height: dimensions.height as f32 };
+        let mut regions = Vec::with_capacity(changed.len());
+
+        for object in changed {
+            let Some(bounds) = bounds(object) else { return Damage::Full };
+            let region = round_out(&(bounds * scale).outset(1.0)).intersection(&window);
+            if !region.is_empty() { regions.push(region) }
+        }
+
+        // Unchanged backdrop blurs sample the damage within their reach, so they have to be redrawn whole
+        let mut backdrops: Vec<(Rect, Rect)> = self.objects.iter()
+        .filter(|(id, object)| previous.get(id).is_some_and(|&old| old == object))
+        .filter_map(|(_, object)| {
+            let (bounds, blur) = (bounds(object)? * scale, backdrop_blur(object)?);
+            Some((round_out(&bounds.outset(1.0)).intersection(&window), bounds.outset(3.0 * blur * scale)))
+        })
+        .collect();
+        // A redrawn backdrop can in turn damage the ones next to it
+        while let Some(index) = backdrops.iter().position(|(_, reach)| regions.iter().any(|region| region.intersects(reach))) {
+            let (area, _) = backdrops.swap_remove(index);
+            if !area.is_empty() { regions.push(area) }
+        }
+
+        match regions.len() {
+            0 => Damage::None,
+            count if count > MAX_REGIONS => Damage::Regions(vec![regions.iter().fold(Rect::default(), |all, region| all.union(region))]),
+            _ => Damage::Regions(regions)
+        }
+    }
+
+    /// Remembers the scene as the last drawn frame
+    pub(crate) fn commit(&mut self, dimensions: Dimensions<u32>, scale: f32, clear_color: u32) {
+        self.drawn = Some(DrawnFrame { objects: self.objects.clone(), dimensions, scale, clear_color });
+    }
+}
+
+/// The largest blur of the backdrop blurs in the object, or `None` if it has none
+fn backdrop_blur(object: &Object) -> Option<f32> {
+    match object {
+        Object::BackdropBlur { blur, .. } => Some(*blur),
+        Object::Group { children, .. } | Object::Clip { children, .. } | Object::Layer { children, .. } => children.iter().filter_map(backdrop_blur).reduce(f32::max),
+        Object::Tagged { object, .. } => backdrop_blur(object),
+        _ => None
+    }
+}
+
+/// Grows the rect to whole pixels
+fn round_out(rect: &Rect) -> Rect {
+    let (x, y) = (rect.x.floor(), rect.y.floor());
+    Rect { x, y, width: (rect.x + rect.width).ceil() - x, height: (rect.y + rect.height).ceil() - y }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{renderer::objects::{Object, Rect}, structs::Dimensions};
+
+    use super::{Damage, RetainedScene};
+
+    const DIMENSIONS: Dimensions<u32> = Dimensions { width: 200, height: 100 };
+
+    fn square(x: f32, y: f32) -> Object {
+        Object::rectangle(x, y, 20.0, 20.0, 0xFFFFFFFF, None)
+    }
+
+    fn damage(scene: &RetainedScene) -> Damage {
+        scene.damage(DIMENSIONS, 1.0, 0, Object::bounds)
+    }
+
+    #[test]
+    fn unchanged_frames_are_skipped() {
+        let mut scene = RetainedScene::new();
+        scene.insert(square(10.0, 10.0));
+        assert_eq!(damage(&scene), Damage::Full);
+
+        scene.commit(DIMENSIONS, 1.0, 0);
+        assert_eq!(damage(&scene), Damage::None);
+    }
+
+    #[test]
+    fn moved_objects_damage_both_areas() {
+        let mut scene = RetainedScene::new();
+        let id = scene.insert(square(10.0, 10.0));
+        scene.commit(DIMENSIONS, 1.0, 0);
+
+        scene.update(id, square(50.0, 10.0));
+        assert_eq!(damage(&scene), Damage::Regions(vec![Object::rect(8.0, 8.0, 24.0, 24.0), Object::rect(48.0, 8.0, 24.0, 24.0)]));
+    }
+
+    #[test]
+    fn inserted_and_removed_objects_are_damaged() {
+        let mut scene = RetainedScene::new();
+        let id = scene.insert(square(10.0, 10.0));
+        scene.commit(DIMENSIONS, 1.0, 0);
+
+        scene.remove(id);
+        scene.insert(square(100.0, 50.0));
+        assert_eq!(damage(&scene), Damage::Regions(vec![Object::rect(98.0, 48.0, 24.0, 24.0), Object::rect(8.0, 8.0, 24.0, 24.0)]));
+    }
+
+    #[test]
+    fn regions_are_scaled() {
+        let mut scene = RetainedScene::new();
+        let id = scene.insert(square(10.0, 10.0));
+        scene.commit(DIMENSIONS, 2.0, 0);
+
+        scene.update(id, Object::rectangle(10.0, 10.0, 20.0, 20.0, 0xFF0000FF, None));
+        assert_eq!(scene.damage(DIMENSIONS, 2.0, 0, Object::bounds), Damage::Regions(vec![Object::rect(17.0, 17.0, 46.0, 46.0); 2]));
+    }
+
+    #[test]
+    fn window_changes_damage_everything() {
+        let mut scene = RetainedScene::new();
+        scene.insert(square(10.0, 10.0));
+        scene.commit(DIMENSIONS, 1.0, 0);
+
+        assert_eq!(scene.damage(Dimensions::new(300, 100), 1.0, 0, Object::bounds), Damage::Full);
+        assert_eq!(scene.damage(DIMENSIONS, 2.0, 0, Object::bounds), Damage::Full);
+        assert_eq!(scene.damage(DIMENSIONS, 1.0, 0xFFFFFFFF, Object::bounds), Damage::Full);
+
+        scene.invalidate();
+        assert_eq!(damage(&scene), Damage::Full);
+    }
+
+    #[test]
+    fn unknown_bounds_damage_everything() {
+        let mut scene = RetainedScene::new();
+        let id = scene.insert(Object::text(10.0, 10.0, "a".to_string(), None, 10.0, 0xFFFFFFFF));
+        scene.commit(DIMENSIONS, 1.0, 0);
+
+        scene.update(id, Object::text(10.0, 10.0, "b".to_string(), None, 10.0, 0xFFFFFFFF));
+        assert_eq!(damage(&scene), Damage::Full);
+
+        // Measured text only damages its own area
+        let measured = scene.damage(DIMENSIONS, 1.0, 0, |object| object.bounds_with(&|_, _, _| Some((30.0, 10.0))));
+        assert_eq!(measured, Damage::Regions(vec![Object::rect(3.0, 3.0, 44.0, 24.0); 2]));
+    }
+
+    #[test]
+    fn backdrops_near_damage_are_redrawn_whole() {
+        let mut scene = RetainedScene::new();
+        let id = scene.insert(square(10.0, 10.0));
+        scene.insert(Object::backdrop_blur(20.0, 0.0, 100.0, 40.0, 4.0, None, None));
+        scene.insert(Object::backdrop_blur(150.0, 0.0, 40.0, 40.0, 4.0, None, None));
+        scene.commit(DIMENSIONS, 1.0, 0);
+
+        // The square only overlaps the first backdrop, which is redrawn with all of its rect
+        scene.update(id, square(12.0, 10.0));
+        assert_eq!(damage(&scene), Damage::Regions(vec![
+            Object::rect(8.0, 8.0, 24.0, 24.0), Object::rect(10.0, 8.0, 24.0, 24.0), Object::rect(18.0, 0.0, 104.0, 42.0)
+        ]));
+    }
+
+    #[test]
+    fn offscreen_changes_are_skipped() {
+        let mut scene = RetainedScene::new();
+        let id = scene.insert(square(300.0, 10.0));
+        scene.commit(DIMENSIONS, 1.0, 0);
+
+        scene.update(id, square(300.0, 50.0));
+        assert_eq!(damage(&scene), Damage::None);
+    }
+
+    #[test]
+    fn many_regions_are_merged() {
+        let mut scene = RetainedScene::new();
+        let ids: Vec<_> = (0..17).map(|i| scene.insert(square(i as f32 * 10.0, 0.0))).collect();
+        scene.commit(DIMENSIONS, 1.0, 0);
+
+        for (i, id) in ids.into_iter().enumerate() {
+            scene.update(id, square(i as f32 * 10.0, 50.0));
+        }
+        assert_eq!(damage(&scene), Damage::Regions(vec![Rect { x: 0.0, y: 0.0, width: 182.0, height: 72.0 }]));
+    }
+}
@@ -1,46 +1,64 @@
 use std::rc::Rc;
 
-use skia_safe::{canvas::Lattice, color_filters, svg::Dom, AlphaType, BlendMode, Canvas, Color4f, ColorType, Data, FilterMode, Font, FontMgr, ImageInfo, Paint, PaintStyle, Point, RRect, Rect, SamplingOptions, TextBlob};
+use log::warn;
+use skia_safe::{canvas::{Lattice, SaveLayerRec, SrcRectConstraint}, gradient_shader, image_filters, shaders, svg::Dom, AlphaType, BlendMode, BlurStyle, Canvas, Color, Color4f, ColorType, Data, FilterMode, Font, FontMgr, ImageInfo, MaskFilter, Matrix, Paint, PaintCap, PaintJoin, PaintStyle, PathEffect, PathFillType, Point, RRect, Rect, SamplingOptions, Shader, TextBlob, TileMode};
 
-use crate::{backend::renderer_data::skia::SkiaRendererData, renderer::{images::{CacheableImage, PixelFormat}, objects, svgs::CacheableSvg}, types::{Object, WindowId}};
+use crate::{backend::renderer_data::skia::SkiaRendererData, renderer::{brushes::{Brush, Extend, GradientKind}, images::{CacheableImage, PixelFormat}, objects, paths::{FillRule, Path, PathElement}, strokes::{LineCap, LineJoin, Stroke}, svgs::CacheableSvg, transforms::Transform}, types::{ClipShape, Dimensions, Object, Rounding, Shadow, WindowId}};
 
-use super::{text::SkiaParapgraph, SkiaRenderer};
+use super::text::{font_metrics, SkiaParapgraph};
 
-pub(crate) fn draw_object(_renderer: &SkiaRenderer, data: &SkiaRendererData, canvas: &Canvas, object: &Object, scale: f32, window_id: WindowId) {
+/// Measures a single line of text as (width, height), or `None` if there is no such font
+pub(crate) fn measure_text(data: &SkiaRendererData, text: &str, font: &Option<String>, size: f32) -> Option<(f32, f32)> {
+    let skia_font = Font::from_typeface(data.get_font(font)?, size);
+    let (width, _) = skia_font.measure_str(text, None);
+    let (line_height, _) = skia_font.metrics();
+
+    Some((width, line_height))
+}
+
+/// The size of the SVG, as it is fitted into the rect of `Object::Svg`
+pub(crate) fn svg_size(data: &SkiaRendererData, svg: &CacheableSvg) -> Option<(f32, f32)> {
+    let size = data.get_or_load_svg_dom(svg).inner().fContainerSize;
+    Some((size.fWidth, size.fHeight))
+}
+
+/// The logical bounds of the object, measuring text with the same font it is drawn with
+pub(crate) fn object_bounds(data: &SkiaRendererData, object: &Object) -> Option<objects::Rect> {
+    object.bounds_with(&|text, font, size| measure_text(data, text, font, size))
+}
+
+pub(crate) fn draw_object(data: &SkiaRendererData, canvas: &Canvas, object: &Object, scale: f32, window_id: Option<&WindowId>) {
     match object {
-        Object::Rectangle { rounding, color, rect } => {
-            let paint = paint(*color, 1.0);
+        Object::Rectangle { rounding, brush, rect, stroke, shadow } => {
+            if let Some(shadow) = shadow {
+                draw_shadow(canvas, rect, rounding.as_ref(), shadow);
+            }
+
+            let paints = [Some(paint(data, brush, 0.0)), stroke.as_ref().map(|stroke| stroke_paint(data, stroke))];
             
             let skia_rect = skia_rect(rect);
 
             if let Some(r) = rounding {
-                let mut rrect = RRect::new_rect(skia_rect);
-            
-                let radii: [Point; 4] = [
-                    (r.top_l, r.top_l),
-                    (r.top_r, r.top_r),
-                    (r.bottom_r, r.bottom_r),
-                    (r.bottom_l, r.bottom_l),
-                ].map(
-                    |(x, y)| (x as i32, y as i32).into()
-                );
-
-                rrect.set_rect_radii(*rrect.rect(), &radii);
+                let rrect = skia_rrect(rect, r);
 
-                canvas.draw_rrect(
-                    rrect,
-                    &paint
-                );
+                for paint in paints.iter().flatten() {
+                    canvas.draw_rrect(
+                        &rrect,
+                        paint
+                    );
+                }
             } else {
-                canvas.draw_rect(
-                    skia_rect,
-                    &paint
-                );
+                for paint in paints.iter().flatten() {
+                    canvas.draw_rect(
+                        skia_rect,
+                        paint
+                    );
+                }
             }
         },
-        Object::Text { text, font, size, color, position } => {
+        Object::Text { text, font, size, brush, position, decoration } => {
             let typeface = data.get_font(font).unwrap();
-            let paint = paint(*color, 0.0);
+            let paint = paint(data, brush, 0.0);
             
             let mut skia_font = Font::from_typeface(typeface, *size);
             skia_font.set_edging(skia_safe::font::Edging::SubpixelAntiAlias);
@@ -53,60 +71,253 @@ pub(crate) fn draw_object(_renderer: &SkiaRenderer, data: &SkiaRendererData, can
 
             canvas.draw_text_blob(
                 text_blob,
-                (position.x as f32, (position.y as f32 + line_height)),
+                (position.x, position.y + line_height),
                 &paint
             );
+
+            if let Some(decoration) = decoration {
+                let (width, _) = skia_font.measure_str(text, None);
+
+                for line in decoration.objects(position.x, position.y + line_height, width, &font_metrics(&skia_font), brush) {
+                    draw_object(data, canvas, &line, scale, window_id);
+                }
+            }
         },
         Object::Paragraph { position, paragraph } => {
-            let paragraph: Rc<SkiaParapgraph> = paragraph.clone().try_into().unwrap();
+            // Deserialized paragraphs have to be resolved with this renderer's data first
+            let paragraph: Result<Rc<SkiaParapgraph>, _> = paragraph.clone().try_into();
+            let Ok(paragraph) = paragraph else {
+                warn!("Skipping a paragraph that wasn't laid out for Skia, resolve it with `Paragraph::resolve` first");
+                return
+            };
 
             paragraph.paragraph.paint(
                 canvas, 
-                (position.x as f32, position.y as f32)
+                (position.x, position.y)
             );
         },
-        Object::Image { rect, image } => {
+        Object::Image { rect, image, source, fit } => {
             let skia_image = data.get_or_load_image(image);
+            let Dimensions { width, height } = *image.dimensions();
+
+            let source = source.as_ref().map_or(Rect::from_wh(width as _, height as _), skia_rect);
+            let (dest, visible) = fit.place((source.width(), source.height()), rect);
+
+            canvas.save();
+            canvas.clip_rect(skia_rect(&visible), None, true);
+
+            canvas.draw_image_rect_with_sampling_options(
+                skia_image,
+                // Strict keeps neighbouring sprites of a sprite sheet from bleeding in
+                Some((&source, SrcRectConstraint::Strict)),
+                skia_rect(&dest),
+                FilterMode::Linear,
+                &Paint::default()
+            );
+
+            canvas.restore();
+        },
+        Object::NinePatch { image, rect, insets, scale } => {
+            let skia_image = data.get_or_load_image(image);
+            let Dimensions { width, height } = *image.dimensions();
+
+            let x_divs = lattice_divs(width, insets.left, insets.right);
+            let y_divs = lattice_divs(height, insets.top, insets.bottom);
 
             let lattice = Lattice {
-                x_divs: &[],
-                y_divs: &[],
+                x_divs: &x_divs,
+                y_divs: &y_divs,
                 rect_types: None,
                 bounds: None,
                 colors: None,
             };
 
+            // Lattices draw the fixed patches at their size in canvas coordinates
+            canvas.save();
+            canvas.translate((rect.x, rect.y));
+            canvas.scale((*scale, *scale));
+
             canvas.draw_image_lattice(
                 skia_image,
                 &lattice,
-                skia_rect(rect),
-                FilterMode::Nearest,
+                Rect::from_wh(rect.width / scale, rect.height / scale),
+                FilterMode::Linear,
                 None
             );
+
+            canvas.restore();
         },
-        Object::Svg { rect, svg, color } => {
-            let rect = rect.clone() * scale;
-            let mut svg = data.get_or_load_svg(svg, canvas, rect.width, rect.height, window_id);
-            let mut paint = paint(*color, 0.0);
+        Object::Svg { rect, svg, brush, fit } => {
+            let mut tint = paint(data, brush, 0.0);
+            tint.set_blend_mode(BlendMode::SrcIn);
 
-            paint.set_color_filter(color_filters::blend(rgba_to_color4f(*color).to_color(), BlendMode::SrcIn));
-            paint.set_anti_alias(true);
-            
+            let svg_size = data.get_or_load_svg_dom(svg).inner().fContainerSize;
+            let (dest, visible) = fit.place((svg_size.fWidth, svg_size.fHeight), rect);
+            let (dest, visible) = (skia_rect(&dest), skia_rect(&visible));
+
+            // The SVG is drawn into its own layer, so the tint keeps its alpha
             canvas.save();
-            canvas.reset_matrix();
-            svg.surface.draw(
-                canvas,
-                (rect.x as f32, rect.y as f32),
-                SamplingOptions::default(),
-                Some(&paint)
+            canvas.clip_rect(visible, None, true);
+            canvas.save_layer(&SaveLayerRec::default().bounds(&visible));
+
+            let matrix = canvas.local_to_device_as_3x3();
+
+            match window_id {
+                // The cached surface can only be used if it doesn't have to be rotated or skewed
+                Some(window_id) if matrix.is_scale_translate() => {
+                    let (physical_rect, _) = matrix.map_rect(dest);
+                    let physical_rect = physical_rect.round();
+                    let mut svg = data.get_or_load_svg(svg, canvas, physical_rect.width() as _, physical_rect.height() as _, window_id.clone());
+
+                    canvas.save();
+                    canvas.reset_matrix();
+                    svg.surface.draw(
+                        canvas,
+                        (physical_rect.left as f32, physical_rect.top as f32),
+                        SamplingOptions::default(),
+                        None
+                    );
+                    canvas.restore();
+                },
+                // Without a window, there is no surface to cache the rasterized SVG for, so render the Dom directly
+                _ => {
+                    let dom = data.get_or_load_svg_dom(svg);
+
+                    canvas.save();
+                    canvas.translate((dest.left, dest.top));
+                    canvas.scale((dest.width() / svg_size.fWidth, dest.height() / svg_size.fHeight));
+                    dom.render(canvas);
+                    canvas.restore();
+                }
+            }
+
+            canvas.draw_rect(visible, &tint);
+            canvas.restore();
+            canvas.restore();
+        },
+        Object::Path { path, brush, stroke } => {
+            let skia_path = skia_path(path);
+
+            canvas.draw_path(
+                &skia_path,
+                &paint(data, brush, 0.0)
             );
+
+            if let Some(stroke) = stroke {
+                canvas.draw_path(
+                    &skia_path,
+                    &stroke_paint(data, stroke)
+                );
+            }
+        },
+        Object::Group { transform, children } => {
+            canvas.save();
+            canvas.concat(&skia_matrix(transform));
+
+            for child in children {
+                draw_object(data, canvas, child, scale, window_id);
+            }
+
+            canvas.restore();
+        },
+        Object::Clip { shape, children } => {
+            canvas.save();
+
+            match shape {
+                ClipShape::Rect(rect) => canvas.clip_rect(skia_rect(rect), None, true),
+                ClipShape::RoundedRect(rect, rounding) => canvas.clip_rrect(skia_rrect(rect, rounding), None, true),
+                ClipShape::Path(path) => canvas.clip_path(&skia_path(path), None, true)
+            };
+
+            for child in children {
+                draw_object(data, canvas, child, scale, window_id);
+            }
+
+            canvas.restore();
+        },
+        Object::Layer { opacity, blend, children } => {
+            let mut paint = Paint::default();
+            paint.set_alpha_f(*opacity);
+            paint.set_blend_mode(skia_blend_mode(*blend));
+
+            canvas.save_layer(&SaveLayerRec::default().paint(&paint));
+
+            for child in children {
+                draw_object(data, canvas, child, scale, window_id);
+            }
+
+            canvas.restore();
+        },
+        Object::Tagged { object, .. } => draw_object(data, canvas, object, scale, window_id),
+        Object::BackdropBlur { rect, rounding, blur, tint } => {
+            canvas.save();
+
+            match rounding {
+                Some(rounding) => canvas.clip_rrect(skia_rrect(rect, rounding), None, true),
+                None => canvas.clip_rect(skia_rect(rect), None, true)
+            };
+
+            // An empty layer with a backdrop filter starts out as the blurred content behind it,
+            // and is drawn back when restored
+            if *blur > 0.0 {
+                let filter = image_filters::blur((*blur, *blur), TileMode::Clamp, None, None)
+                .expect("Blur filters with a positive sigma are always valid");
+
+                canvas.save_layer(&SaveLayerRec::default().backdrop(&filter));
+                canvas.restore();
+            }
+
+            if let Some(tint) = tint {
+                canvas.draw_paint(&paint(data, tint, 0.0));
+            }
+
             canvas.restore();
         }
     }
 }
 
-pub(crate) fn paint(color: u32, width: f32) -> Paint {
-    let mut paint = Paint::new(rgba_to_color4f(color), None);
+/// The divisions of a lattice along one axis, which Skia needs to be strictly increasing and inside of the image.
+/// A division at 0 is kept, as Skia then stretches the first column instead of keeping it fixed.
+fn lattice_divs(length: u32, start: u32, end: u32) -> Vec<i32> {
+    let start = start.min(length);
+    let end = length - end.min(length - start);
+
+    let mut divs: Vec<i32> = [start, end]
+    .into_iter()
+    .filter(|&div| div < length)
+    .map(|div| div as i32)
+    .collect();
+    divs.dedup();
+
+    divs
+}
+
+fn draw_shadow(canvas: &Canvas, rect: &objects::Rect, rounding: Option<&Rounding>, shadow: &Shadow) {
+    let ((x, y, width, height), radii) = shadow.shape(rect, rounding);
+
+    let mut paint = Paint::new(rgba_to_color4f(shadow.color), None);
+    paint.set_anti_alias(true);
+    if shadow.blur > 0.0 {
+        paint.set_mask_filter(MaskFilter::blur(BlurStyle::Normal, shadow.blur / 2.0, None));
+    }
+
+    let radii: [Point; 4] = radii.map(|radius| (radius, radius).into());
+
+    canvas.draw_rrect(
+        RRect::new_rect_radii(Rect::from_xywh(x, y, width, height), &radii),
+        &paint
+    );
+}
+
+pub(crate) fn paint(data: &SkiaRendererData, brush: &Brush, width: f32) -> Paint {
+    let mut paint = match brush {
+        Brush::Solid(color) => Paint::new(rgba_to_color4f(*color), None),
+        _ => {
+            let mut paint = Paint::default();
+            paint.set_shader(brush_shader(data, brush));
+            paint
+        }
+    };
     paint.set_anti_alias(true);
     paint.set_style(PaintStyle::Fill);
     paint.set_stroke_width(width);
@@ -114,11 +325,144 @@ pub(crate) fn paint(color: u32, width: f32) -> Paint {
     paint
 }
 
+pub(crate) fn stroke_paint(data: &SkiaRendererData, stroke: &Stroke) -> Paint {
+    let mut paint = paint(data, &stroke.brush, stroke.width);
+    paint.set_style(PaintStyle::Stroke);
+    paint.set_stroke_miter(stroke.miter_limit);
+    paint.set_stroke_cap(match stroke.cap {
+        LineCap::Butt => PaintCap::Butt,
+        LineCap::Round => PaintCap::Round,
+        LineCap::Square => PaintCap::Square
+    });
+    paint.set_stroke_join(match stroke.join {
+        LineJoin::Miter => PaintJoin::Miter,
+        LineJoin::Round => PaintJoin::Round,
+        LineJoin::Bevel => PaintJoin::Bevel
+    });
+
+    if let Some(dash) = stroke.dash_pattern() {
+        paint.set_path_effect(PathEffect::dash(&dash, stroke.dash_offset));
+    }
+
+    paint
+}
+
+fn brush_shader(data: &SkiaRendererData, brush: &Brush) -> Option<Shader> {
+    match brush {
+        Brush::Solid(color) => Some(shaders::color(rgba_to_color4f(*color).to_color())),
+        Brush::Gradient(gradient) => {
+            let colors: Vec<Color> = gradient.stops.iter().map(|stop| rgba_to_color4f(stop.color).to_color()).collect();
+            let positions: Vec<f32> = gradient.stops.iter().map(|stop| stop.offset).collect();
+            let mode = match gradient.extend {
+                Extend::Pad => TileMode::Clamp,
+                Extend::Repeat => TileMode::Repeat,
+                Extend::Reflect => TileMode::Mirror
+            };
+
+            match gradient.kind {
+                GradientKind::Linear { start, end } => gradient_shader::linear(
+                    (Point::from(start), Point::from(end)),
+                    colors.as_slice(),
+                    positions.as_slice(),
+                    mode,
+                    None,
+                    None
+                ),
+                GradientKind::Radial { center, radius } => gradient_shader::radial(
+                    Point::from(center),
+                    radius,
+                    colors.as_slice(),
+                    positions.as_slice(),
+                    mode,
+                    None,
+                    None
+                ),
+                GradientKind::Sweep { center, start_angle, end_angle } => gradient_shader::sweep(
+                    Point::from(center),
+                    colors.as_slice(),
+                    positions.as_slice(),
+                    mode,
+                    (start_angle.to_degrees(), end_angle.to_degrees()),
+                    None,
+                    None
+                )
+            }
+        },
+        Brush::Image(pattern) => {
+            let mut matrix = Matrix::translate((pattern.x, pattern.y));
+            matrix.pre_scale((pattern.scale, pattern.scale), None);
+
+            data.get_or_load_image(&pattern.image).to_shader(
+                (TileMode::Repeat, TileMode::Repeat),
+                SamplingOptions::default(),
+                &matrix
+            )
+        }
+    }
+}
+
+pub(crate) fn skia_blend_mode(blend: objects::BlendMode) -> BlendMode {
+    match blend {
+        objects::BlendMode::Normal => BlendMode::SrcOver,
+        objects::BlendMode::Multiply => BlendMode::Multiply,
+        objects::BlendMode::Screen => BlendMode::Screen,
+        objects::BlendMode::Overlay => BlendMode::Overlay,
+        objects::BlendMode::Darken => BlendMode::Darken,
+        objects::BlendMode::Lighten => BlendMode::Lighten,
+        objects::BlendMode::ColorDodge => BlendMode::ColorDodge,
+        objects::BlendMode::ColorBurn => BlendMode::ColorBurn,
+        objects::BlendMode::HardLight => BlendMode::HardLight,
+        objects::BlendMode::SoftLight => BlendMode::SoftLight,
+        objects::BlendMode::Difference => BlendMode::Difference,
+        objects::BlendMode::Exclusion => BlendMode::Exclusion,
+        objects::BlendMode::Hue => BlendMode::Hue,
+        objects::BlendMode::Saturation => BlendMode::Saturation,
+        objects::BlendMode::Color => BlendMode::Color,
+        objects::BlendMode::Luminosity => BlendMode::Luminosity
+    }
+}
+
+pub(crate) fn skia_matrix(transform: &Transform) -> Matrix {
+    let Transform { sx, ky, kx, sy, tx, ty } = *transform;
+    Matrix::new_all(sx, kx, tx, ky, sy, ty, 0.0, 0.0, 1.0)
+}
+
+pub(crate) fn skia_rrect(rect: &objects::Rect, rounding: &Rounding) -> RRect {
+    let radii: [Point; 4] = [
+        rounding.top_l,
+        rounding.top_r,
+        rounding.bottom_r,
+        rounding.bottom_l,
+    ].map(
+        |radius| (radius, radius).into()
+    );
+
+    RRect::new_rect_radii(skia_rect(rect), &radii)
+}
+
 pub(crate) fn skia_rect(rect: &objects::Rect) -> Rect {
-    Rect::from_point_and_size(
-        (rect.x, rect.y), 
-        (rect.width as i32, rect.height as i32)
-    )
+    Rect::from_xywh(rect.x, rect.y, rect.width, rect.height)
+}
+
+pub(crate) fn skia_path(path: &Path) -> skia_safe::Path {
+    let mut skia_path = skia_safe::Path::new();
+
+    for element in path.elements() {
+        match element {
+            PathElement::MoveTo(x, y) => skia_path.move_to((x, y)),
+            PathElement::LineTo(x, y) => skia_path.line_to((x, y)),
+            PathElement::QuadTo(cx, cy, x, y) => skia_path.quad_to((cx, cy), (x, y)),
+            PathElement::CubicTo(cx1, cy1, cx2, cy2, x, y) => skia_path.cubic_to((cx1, cy1), (cx2, cy2), (x, y)),
+            PathElement::Close => skia_path.close()
+        };
+    }
+
+    skia_path.set_fill_type(match path.fill_rule {
+        FillRule::NonZero => PathFillType::Winding,
+        FillRule::EvenOdd => PathFillType::EvenOdd
+    });
+
+    skia_path
 }
 
 pub(crate) fn rgba_to_color4f(color: u32) -> Color4f {
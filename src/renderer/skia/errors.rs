@@ -6,6 +6,10 @@ use thiserror::Error;
 pub enum SkiaRendererError {
     #[error("No Skia rendering backend could be created")]
     NoBackend,
+    #[error("Failed to create a Skia raster surface")]
+    RasterSurface,
+    #[error("Failed to read back the pixels of a Skia surface")]
+    ReadPixels,
     #[cfg(feature = "skia-vulkan")]
     #[error(transparent)]
     Vulkan(#[from] VulkanErr)
@@ -1,13 +1,13 @@
-use std::{cell::RefCell, collections::HashMap};
+use std::cell::{Cell, RefCell};
 
 use crate::{
     backend::{
         renderer_data::{skia::SkiaRendererData, RendererData},
         windowing::window::{Window, WindowTrait},
-    }, traits::RendererDataTrait, types::Object
+    }, traits::RendererDataTrait, types::{CacheableImage, Damage, Dimensions, Object, PixelFormat, Rect, RetainedScene}
 };
 
-use super::{errors::RendererError, RResult, RendererTrait};
+use super::{errors::RendererError, overlay::DebugOverlay, RResult, RendererTrait, DEFAULT_CLEAR_COLOR};
 
 pub mod adapter;
 pub mod errors;
@@ -21,7 +21,7 @@ pub mod vulkan;
 use enum_dispatch::enum_dispatch;
 use errors::SkiaRendererError;
 use log::warn;
-use skia_safe::{textlayout::{FontCollection, TypefaceFontProvider}, Canvas, Color4f, FontMgr};
+use skia_safe::{image::CachingHint, surfaces, AlphaType, BlendMode, Canvas, ClipOp, Color4f, ColorType, ImageInfo, Paint, Path, SamplingOptions, Surface};
 use strum::{EnumIter, IntoEnumIterator};
 
 #[cfg(feature = "skia-opengl")]
@@ -32,50 +32,144 @@ use vulkan::SkiaVulkanBackend;
 #[derive(Debug)]
 pub struct SkiaRenderer {
     skia_backend: SkiaRenderingBackends,
+    clear_color: Cell<u32>,
+    overlay: DebugOverlay,
+    /// The last frame drawn with `render_scene`, as the window's own surfaces don't keep their content
+    retained: RefCell<Option<Surface>>
 }
 
 impl SkiaRenderer {
     pub fn new(window: &Window) -> RResult<Self> {
         Ok(SkiaRenderer {
             skia_backend: SkiaRenderingBackends::create(window)?,
+            clear_color: Cell::new(DEFAULT_CLEAR_COLOR),
+            overlay: DebugOverlay::new(),
+            retained: RefCell::new(None)
         })
     }
 }
 
 impl RendererTrait for SkiaRenderer {
+    fn set_clear_color(&self, color: u32) {
+        self.clear_color.set(color);
+    }
+
+    fn clear_color(&self) -> u32 {
+        self.clear_color.get()
+    }
+
+    fn set_debug_overlay(&self, enabled: bool) {
+        self.overlay.set_enabled(enabled);
+    }
+
+    fn debug_overlay(&self) -> bool {
+        self.overlay.is_enabled()
+    }
+
     fn render(&self, window: &Window, data: &RendererData, objects: Vec<&Object>) -> RResult<()> {
         self.skia_backend.render(window, |canvas: &Canvas| {
-            canvas.draw_color(Color4f::new(0.1, 0.1, 0.1, 1.0), None);
+            // Clearing replaces the previous frame, even if the color is transparent
+            canvas.clear(adapter::rgba_to_color4f(self.clear_color.get()));
 
             let scale = window.current_scale();
             canvas.scale((scale, scale));
 
             let skia_data = data.try_as_skia_ref().unwrap();
 
-            for object in objects {
-                adapter::draw_object(self, skia_data, canvas, object, scale, window.id());
+            for object in &objects {
+                adapter::draw_object(skia_data, canvas, object, scale, Some(&window.id()));
+            }
+
+            let overlay = self.overlay.objects(&objects, skia_data.default_font.borrow().is_some(), |object| adapter::object_bounds(skia_data, object));
+            for object in &overlay {
+                adapter::draw_object(skia_data, canvas, object, scale, Some(&window.id()));
             }
         })
     }
 
+    /// Keeps the scene in a surface of its own, so only the damaged regions are redrawn into it before it is copied to the window
+    fn render_scene(&self, window: &Window, data: &RendererData, scene: &mut RetainedScene) -> RResult<()> {
+        let dimensions = window.physical_dimensions();
+        let (scale, clear_color) = (window.current_scale(), self.clear_color.get());
+        let skia_data = data.try_as_skia_ref().unwrap();
+
+        // The debug overlay changes every frame
+        if self.overlay.is_enabled() {
+            self.retained.take();
+            self.render(window, data, scene.objects())?;
+            scene.commit(dimensions, scale, clear_color);
+            return Ok(())
+        }
+
+        let damage = scene.damage(dimensions, scale, clear_color, |object| adapter::object_bounds(skia_data, object));
+        if damage == Damage::None { return Ok(()) }
+
+        let objects = scene.objects();
+        self.skia_backend.render(window, |canvas: &Canvas| {
+            let mut retained = self.retained.borrow_mut();
+            // A new or resized surface has no content yet
+            let damage = match &*retained {
+                Some(surface) if (surface.width(), surface.height()) == (dimensions.width as i32, dimensions.height as i32) => damage,
+                _ => {
+                    *retained = canvas.new_surface(&ImageInfo::new_n32_premul((dimensions.width as i32, dimensions.height as i32), None), None);
+                    Damage::Full
+                }
+            };
+            let Some(surface) = retained.as_mut() else {
+                // Without a surface of its own, the whole scene is drawn to the window like with `render`
+                canvas.clear(adapter::rgba_to_color4f(clear_color));
+                canvas.scale((scale, scale));
+                for object in &objects {
+                    adapter::draw_object(skia_data, canvas, object, scale, Some(&window.id()));
+                }
+                return
+            };
+
+            let target = surface.canvas();
+            target.save();
+
+            let regions = match &damage {
+                Damage::Regions(regions) => {
+                    let mut clip = Path::new();
+                    for region in regions {
+                        clip.add_rect(adapter::skia_rect(region), None);
+                    }
+                    target.clip_path(&clip, ClipOp::Intersect, false);
+                    Some(regions)
+                },
+                _ => None
+            };
+
+            // Clearing only replaces the clipped regions
+            target.clear(adapter::rgba_to_color4f(clear_color));
+            target.scale((scale, scale));
+
+            for object in &objects {
+                // Everything outside of the regions would be clipped away anyway
+                if let (Some(regions), Some(bounds)) = (regions, adapter::object_bounds(skia_data, object)) {
+                    let bounds: Rect = bounds * scale;
+                    if !regions.iter().any(|region| bounds.intersects(region)) { continue }
+                }
+
+                adapter::draw_object(skia_data, target, object, scale, Some(&window.id()));
+            }
+
+            target.restore();
+
+            let mut copy = Paint::default();
+            copy.set_blend_mode(BlendMode::Src);
+            surface.draw(canvas, (0.0, 0.0), SamplingOptions::default(), Some(&copy));
+        })?;
+
+        scene.commit(dimensions, scale, clear_color);
+        Ok(())
+    }
+
     fn transform_data(&self, data: &RendererData) -> Option<RendererData> {
         match data {
             RendererData::Placeholder(placeholder) => {
-                let mut font_collection = FontCollection::new();
-                let font_mgr = FontMgr::new();
-                let font_provider = TypefaceFontProvider::new();
-                font_collection.set_default_font_manager(Some(font_provider.clone().into()), None);
-                    
-                let new = SkiaRendererData {
-                    font_map: RefCell::new(HashMap::new()),
-                    font_mgr,
-                    font_collection,
-                    font_provider,
-                    default_font: RefCell::new(None),
-                    image_cache: RefCell::new(HashMap::new()),
-                    svg_cache: RefCell::new(HashMap::new()),
-                };
-            
+                let new = SkiaRendererData::new();
+
                 let default_index = placeholder.default_index.get() as usize;
                 for (index, (alias, bytes)) in placeholder.fonts.borrow_mut().drain(..).enumerate() {
                     if index == default_index {
@@ -92,10 +186,36 @@ impl RendererTrait for SkiaRenderer {
     }
 
     fn recreate(&self, window: &Window, _renderer_data: &RendererData) {
+        self.retained.take();
         self.skia_backend.recreate(window)
     }
 }
 
+/// Renders the objects into an image using a CPU raster surface, without needing a window.
+pub(crate) fn render_offscreen(data: &SkiaRendererData, objects: Vec<&Object>, dimensions: Dimensions<u32>, scale: f32) -> RResult<CacheableImage> {
+    let Dimensions { width, height } = dimensions;
+    if width == 0 || height == 0 { return Err(RendererError::ZeroSize) }
+
+    let surface_info = ImageInfo::new((width as i32, height as i32), ColorType::RGBA8888, AlphaType::Premul, None);
+    let mut surface = surfaces::raster(&surface_info, None, None).ok_or(SkiaRendererError::RasterSurface)?;
+
+    let canvas = surface.canvas();
+    canvas.clear(Color4f::new(0.0, 0.0, 0.0, 0.0));
+    canvas.scale((scale, scale));
+
+    for object in objects {
+        adapter::draw_object(data, canvas, object, scale, None);
+    }
+
+    let info = surface_info.with_alpha_type(AlphaType::Unpremul);
+    let mut pixels = vec![0u8; info.compute_byte_size(info.min_row_bytes())];
+    if !surface.image_snapshot().read_pixels(&info, &mut pixels, info.min_row_bytes(), (0, 0), CachingHint::Allow) {
+        return Err(SkiaRendererError::ReadPixels.into())
+    }
+
+    Ok(CacheableImage::new(pixels.into(), PixelFormat::RGBA8, dimensions))
+}
+
 #[derive(Debug, EnumIter)]
 pub enum SkiaRenderingBackendTypes {
     #[cfg(feature = "skia-vulkan")]
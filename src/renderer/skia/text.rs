@@ -1,79 +1,162 @@
 use std::{num::NonZeroU32, rc::Rc};
 
-use skia_safe::{font_arguments::{variation_position::Coordinate, VariationPosition}, font_style::{Slant, Weight, Width}, textlayout::{self, ParagraphBuilder, ParagraphStyle, TextStyle}, Font, FontArguments, FontStyle, FourByteTag};
+use skia_safe::{font_arguments::{variation_position::Coordinate, VariationPosition}, font_style::{Slant, Weight, Width}, textlayout::{self, ParagraphBuilder, ParagraphStyle, PlaceholderAlignment, PlaceholderStyle, TextBaseline, TextDecorationMode, TextDecorationStyle, TextStyle}, Font, FontArguments, FontStyle, FourByteTag};
 
-use crate::{backend::renderer_data::skia::SkiaRendererData, renderer::text::{ParagraphTrait, TextOptions, TextOverflow, TextWrap}};
+use crate::{backend::renderer_data::skia::SkiaRendererData, renderer::{brushes::Brush, text::{DecorationStyle, FontMetrics, ParagraphTrait, SpanStyle, TextAlign, TextMetrics, TextOptions, TextOverflow, TextSpan}}};
 
-use super::adapter::paint;
+use super::adapter::{paint, rgba_to_color4f};
 
 
 #[derive(Debug)]
 pub struct SkiaParapgraph {
     pub options: TextOptions,
+    text: String,
+    spans: Vec<TextSpan>,
+    max_height: Option<NonZeroU32>,
     pub(crate) paragraph: textlayout::Paragraph
 }
 
 impl SkiaParapgraph {
-    pub fn new(data: &SkiaRendererData, text: String, width: u32, max_height: Option<NonZeroU32>, options: TextOptions) -> Self {
-        let mut paragraph_style = ParagraphStyle::new();
-        let mut text_style = TextStyle::new();
-        let paint = paint(options.color, 0.0);
-        let typeface = data.get_font(&options.font);
-
-        let var_coords = VariationPosition {
-            coordinates: &make_var_coords(&[("wght", options.weight as f32)])
-        };
-        let arguments = FontArguments::new().set_variation_design_position(var_coords);
-
-        if let Some(typeface) = &typeface {
-            text_style.set_typeface(typeface.clone_with_arguments(&arguments));
-            text_style.set_font_families(&[typeface.family_name()]);
+    pub fn new(data: &SkiaRendererData, spans: Vec<TextSpan>, width: u32, max_height: Option<NonZeroU32>, options: TextOptions) -> Self {
+        let mut paragraph = build_paragraph(data, &spans, width, &options, options.max_lines.map(|max_lines| max_lines.get() as usize));
+
+        // Like in the other renderers, every line is as high as its highest span and only lines that end above the max height are shown.
+        // At least one line is always shown.
+        if let Some(max_height) = max_height {
+            let fitting = paragraph.get_line_metrics().iter()
+            .scan(0.0, |bottom, line| { *bottom += line.height; Some(*bottom) })
+            .take_while(|bottom| *bottom <= max_height.get() as f64)
+            .count()
+            .max(1);
+
+            if fitting < paragraph.line_number() {
+                paragraph = build_paragraph(data, &spans, width, &options, Some(fitting));
+            }
         }
 
-        
+        Self {
+            options,
+            text: spans.iter().map(|span| span.text.as_str()).collect(),
+            spans,
+            max_height,
+            paragraph
+        }
+    }
+}
 
-        text_style
-        .set_foreground_paint(&paint)
-        .set_font_size(options.size)
-        .set_font_style(FontStyle::new(
-            Weight::from(options.weight as i32),
-            Width::NORMAL,
-            if options.italic {
-                Slant::Italic
-            } else {
-                Slant::Upright
-            }
-        ))
-        .set_font_arguments(&arguments);
+/// Builds and lays out the paragraph, cutting it off after `max_lines`
+fn build_paragraph(data: &SkiaRendererData, spans: &[TextSpan], width: u32, options: &TextOptions, max_lines: Option<usize>) -> textlayout::Paragraph {
+    let mut paragraph_style = ParagraphStyle::new();
+    paragraph_style.set_text_align(match options.align {
+        TextAlign::Left => textlayout::TextAlign::Left,
+        TextAlign::Center => textlayout::TextAlign::Center,
+        TextAlign::Right => textlayout::TextAlign::Right,
+        TextAlign::Justify => textlayout::TextAlign::Justify
+    });
+
+    if options.overflow == TextOverflow::Elide {
+        paragraph_style.set_ellipsis("…");
+    }
+    
+    if let Some(max_lines) = max_lines {
+        paragraph_style.set_max_lines(max_lines);
+    }
+    
+    paragraph_style.set_text_style(&text_style(data, &options.span_style(), options));
 
+    let mut paragraph_builder = ParagraphBuilder::new(&paragraph_style, data.font_collection.clone());
+    // Skia has no first line indent or tab stops, so both are empty space. Tabs always take a whole tab width.
+    let space = |width: f32| PlaceholderStyle::new(width, 0.0, PlaceholderAlignment::Baseline, TextBaseline::Alphabetic, 0.0);
 
-        if options.overflow == TextOverflow::Elide {
-            paragraph_style.set_ellipsis("…");
-        }
-        
-        if let Some(max_h) = max_height {
-            if options.wrap == TextWrap::NoWrap {
-                paragraph_style.set_max_lines(1);
-            } else {
-                let font = Font::from_typeface(typeface.expect("No font found"), options.size);
-                let (line_height, _) = font.metrics();
-                paragraph_style.set_max_lines((max_h.get() as f32 / line_height).floor() as usize);
-            }
+    if options.indent > 0.0 {
+        paragraph_builder.add_placeholder(&space(options.indent));
+    }
+
+    for span in spans {
+        paragraph_builder.push_style(&text_style(data, &span.style, options));
+
+        for (index, part) in span.text.split('\t').enumerate() {
+            if index > 0 { paragraph_builder.add_placeholder(&space(options.tab_width)); }
+            paragraph_builder.add_text(part);
         }
-        
-        paragraph_style.set_text_style(&text_style);
 
-        let mut paragraph_builder = ParagraphBuilder::new(&paragraph_style, data.font_collection.clone());
-        paragraph_builder.push_style(&text_style);
-        paragraph_builder.add_text(text);
+        paragraph_builder.pop();
+    }
+
+    let mut paragraph = paragraph_builder.build();
+    paragraph.layout(width as _);
+    paragraph
+}
 
-        let mut paragraph = paragraph_builder.build();
-        paragraph.layout(width as _);
+fn text_style(data: &SkiaRendererData, style: &SpanStyle, options: &TextOptions) -> TextStyle {
+    let mut text_style = TextStyle::new();
+    let paint = paint(data, &style.brush, 0.0);
+    let typeface = data.get_font(&style.font);
 
-        Self {
-            options,
-            paragraph
+    let var_coords = VariationPosition {
+        coordinates: &make_var_coords(&[("wght", style.weight as f32)])
+    };
+    let arguments = FontArguments::new().set_variation_design_position(var_coords);
+
+    if let Some(typeface) = &typeface {
+        text_style.set_typeface(typeface.clone_with_arguments(&arguments));
+        text_style.set_font_families(&[typeface.family_name()]);
+    }
+
+    text_style
+    .set_foreground_paint(&paint)
+    .set_font_size(style.size)
+    .set_font_style(FontStyle::new(
+        Weight::from(style.weight as i32),
+        Width::NORMAL,
+        if style.italic {
+            Slant::Italic
+        } else {
+            Slant::Upright
         }
+    ))
+    .set_font_arguments(&arguments)
+    .set_letter_spacing(options.letter_spacing)
+    .set_word_spacing(options.word_spacing);
+
+    if let Some(line_height) = options.line_height {
+        text_style.set_height_override(true).set_height(line_height);
+    }
+
+    if let Some(decoration) = &style.decoration {
+        let mut decoration_type = textlayout::TextDecoration::NO_DECORATION;
+        decoration_type.set(textlayout::TextDecoration::UNDERLINE, decoration.underline);
+        decoration_type.set(textlayout::TextDecoration::LINE_THROUGH, decoration.strikethrough);
+        decoration_type.set(textlayout::TextDecoration::OVERLINE, decoration.overline);
+
+        // Skia scales the thickness the font suggests
+        let multiplier = decoration.thickness.zip(typeface).map_or(1.0, |(thickness, typeface)| {
+            let suggested = font_metrics(&Font::from_typeface(typeface, style.size)).underline_thickness;
+            if suggested > 0.0 { thickness / suggested } else { 1.0 }
+        });
+
+        text_style
+        .set_decoration_type(decoration_type)
+        .set_decoration_mode(TextDecorationMode::Through)
+        .set_decoration_style(match decoration.style {
+            DecorationStyle::Solid => TextDecorationStyle::Solid,
+            DecorationStyle::Dotted => TextDecorationStyle::Dotted,
+            DecorationStyle::Dashed => TextDecorationStyle::Dashed,
+            DecorationStyle::Wavy => TextDecorationStyle::Wavy
+        })
+        .set_decoration_color(rgba_to_color4f(decoration_color(decoration.brush.as_ref().unwrap_or(&style.brush))).to_color())
+        .set_decoration_thickness_multiplier(multiplier);
+    }
+
+    text_style
+}
+
+/// Skia only draws decorations with a color, so gradients use their first color and images black
+fn decoration_color(brush: &Brush) -> u32 {
+    match brush {
+        Brush::Solid(color) => *color,
+        Brush::Gradient(gradient) => gradient.stops.first().map_or(0x000000FF, |stop| stop.color),
+        Brush::Image(_) => 0x000000FF
     }
 }
 
@@ -82,11 +165,72 @@ impl ParagraphTrait for Rc<SkiaParapgraph> {
         &self.options
     }
     
+    fn text(&self) -> &str {
+        &self.text
+    }
+
+    fn spans(&self) -> &[TextSpan] {
+        &self.spans
+    }
+
+    fn max_height(&self) -> Option<NonZeroU32> {
+        self.max_height
+    }
+
+    fn width(&self) -> f32 {
+        self.paragraph.max_width()
+    }
+
     fn height(&self) -> f32 {
         self.paragraph.height()
     }
 }
 
+/// Measures text like `Objects::Text` draws it, with the weight and spacing of the options
+pub(crate) fn measure_text(data: &SkiaRendererData, text: &str, options: &TextOptions) -> Option<TextMetrics> {
+    let typeface = data.get_font(&options.font)?;
+    let var_coords = VariationPosition {
+        coordinates: &make_var_coords(&[("wght", options.weight as f32)])
+    };
+    let arguments = FontArguments::new().set_variation_design_position(var_coords);
+
+    let font = Font::from_typeface(typeface.clone_with_arguments(&arguments).unwrap_or(typeface), options.size);
+    let metrics = font_metrics(&font);
+
+    let width = text.split('\n').map(|line| {
+        let spacing = line.chars().map(|ch| if ch.is_whitespace() { options.letter_spacing + options.word_spacing } else { options.letter_spacing }).sum::<f32>();
+        font.measure_str(line, None).0 + spacing
+    }).fold(0.0, f32::max);
+
+    Some(TextMetrics {
+        width,
+        height: metrics.line_height * text.split('\n').count() as f32,
+        ascent: metrics.ascent,
+        descent: metrics.descent,
+        // Text is drawn with its baseline one line height below its position
+        baseline: metrics.line_height
+    })
+}
+
+pub(crate) fn font_metrics(font: &Font) -> FontMetrics {
+    let (line_height, metrics) = font.metrics();
+
+    // Fonts without decoration metrics get the same lines as in the other renderers
+    let size = font.size();
+
+    FontMetrics {
+        // Skia's ascent is negative, as it is above the baseline
+        ascent: -metrics.ascent,
+        descent: metrics.descent,
+        leading: metrics.leading,
+        line_height,
+        underline_offset: metrics.underline_position().unwrap_or(metrics.descent / 2.0),
+        underline_thickness: metrics.underline_thickness().unwrap_or(size / 12.0),
+        strikeout_offset: metrics.strikeout_position().unwrap_or(metrics.ascent * 0.3),
+        strikeout_thickness: metrics.strikeout_thickness().unwrap_or(size / 12.0)
+    }
+}
+
 fn make_var_coords(from: &[(&str, f32)]) -> Vec<Coordinate> {
     let coordinates: Vec<Coordinate> = from.iter().map(|(axis, val)| {
         let b = axis.as_bytes();
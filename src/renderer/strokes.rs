@@ -0,0 +1,105 @@
+use std::{f32::consts::SQRT_2, ops::Mul};
+
+use super::brushes::Brush;
+
+
+/// Describes how the outline of a shape is drawn
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stroke {
+    pub width: f32,
+    pub brush: Brush,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    /// Limit of the ratio between miter length and stroke width, before `LineJoin::Miter` falls back to a bevel
+    pub miter_limit: f32,
+    /// Alternating lengths of dashes and gaps. Empty for a solid line.
+    pub dash: Vec<f32>,
+    pub dash_offset: f32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+    Square
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel
+}
+
+impl Stroke {
+    #[inline]
+    pub fn new(width: f32, brush: impl Into<Brush>) -> Self {
+        Self {
+            width,
+            brush: brush.into(),
+            cap: LineCap::default(),
+            join: LineJoin::default(),
+            miter_limit: 4.0,
+            dash: Vec::new(),
+            dash_offset: 0.0
+        }
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub fn with_miter_limit(mut self, miter_limit: f32) -> Self {
+        self.miter_limit = miter_limit;
+        self
+    }
+
+    pub fn with_dash(mut self, dash: Vec<f32>, offset: f32) -> Self {
+        self.dash = dash;
+        self.dash_offset = offset;
+        self
+    }
+
+    /// How far the stroke can reach outside of the outlined shape, at its joins and caps
+    pub(crate) fn outset(&self) -> f32 {
+        let join = if self.join == LineJoin::Miter { self.miter_limit.max(SQRT_2) } else { SQRT_2 };
+        self.width / 2.0 * join
+    }
+
+    /// The dash pattern with an even number of entries, as expected by the renderers.
+    /// Like in SVG, odd patterns are repeated once. Returns `None` for solid lines.
+    pub(crate) fn dash_pattern(&self) -> Option<Vec<f32>> {
+        if self.dash.is_empty() || self.dash.iter().all(|&length| length <= 0.0) { return None }
+
+        if self.dash.len() % 2 == 1 {
+            Some(self.dash.repeat(2))
+        } else {
+            Some(self.dash.clone())
+        }
+    }
+}
+
+impl Mul<f32> for Stroke {
+    type Output = Self;
+
+    fn mul(self, with: f32) -> Self::Output {
+        Self {
+            width: self.width * with,
+            brush: self.brush * with,
+            dash: self.dash.into_iter().map(|length| length * with).collect(),
+            dash_offset: self.dash_offset * with,
+            ..self
+        }
+    }
+}
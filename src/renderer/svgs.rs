@@ -30,4 +30,26 @@ impl CacheableSvg {
     pub fn uuid(&self) -> &Uuid {
         &self.uuid
     }
-}
\ No newline at end of file
+}
+
+/// SVGs are compared by their Uuid, so comparing never looks at the bytes
+impl PartialEq for CacheableSvg {
+    fn eq(&self, other: &Self) -> bool {
+        self.uuid == other.uuid
+    }
+}
+
+/// Inside of a capture, the bytes are only written once and later frames refer to the SVG by its Uuid
+#[cfg(feature = "serde")]
+impl serde::Serialize for CacheableSvg {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        super::capture::serialize_asset(self.uuid, &self.bytes, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CacheableSvg {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        super::capture::deserialize_asset(deserializer, |uuid, bytes| Self { bytes, uuid })
+    }
+}
@@ -1,9 +1,11 @@
-use std::{num::NonZeroU32, rc::Rc};
+use std::{f32::consts::TAU, num::NonZeroU32, ops::Mul, rc::Rc};
 
 use enum_dispatch::enum_dispatch;
 
 use crate::backend::renderer_data::RendererData;
 
+use super::{brushes::Brush, objects::Object, paths::Path, strokes::{LineCap, Stroke}};
+
 
 #[derive(Debug, Clone)]
 #[enum_dispatch(ParagraphTrait)]
@@ -13,48 +15,236 @@ pub enum Paragraph {
     #[cfg(feature = "r-vello")]
     Vello(Rc<super::vello::text::VelloParagraph>),
     #[cfg(feature = "r-skia")]
-    Skia(Rc<super::skia::text::SkiaParapgraph>)
+    Skia(Rc<super::skia::text::SkiaParapgraph>),
+    #[cfg(feature = "r-tiny-skia")]
+    TinySkia(Rc<super::tiny_skia::text::TinySkiaParagraph>),
+    /// A deserialized paragraph, which has to be laid out again with `Paragraph::resolve` before it can be drawn
+    #[cfg(feature = "serde")]
+    Unresolved(Rc<UnresolvedParagraph>)
 }
 
 impl Paragraph {
     pub fn new(renderer_data: &RendererData, text: String, width: u32, max_height: Option<NonZeroU32>, options: TextOptions) -> Self {
+        let spans = vec![TextSpan::new(text, options.span_style())];
+
+        Self::new_rich(renderer_data, spans, width, max_height, options)
+    }
+
+    /// Lays out the spans as one paragraph, each with its own style.
+    /// Wrapping and overflow come from the options, see `RichText` for building the spans.
+    pub fn new_rich(renderer_data: &RendererData, spans: Vec<TextSpan>, width: u32, max_height: Option<NonZeroU32>, options: TextOptions) -> Self {
         match renderer_data {
             #[cfg(feature = "r-skia")]
             RendererData::Skia(data) => Self::Skia(Rc::new(
-                super::skia::text::SkiaParapgraph::new(data, text, width, max_height, options)
+                super::skia::text::SkiaParapgraph::new(data, spans, width, max_height, options)
             )),
             #[cfg(feature = "r-vello")]
             RendererData::Vello(data) => Self::Vello(Rc::new(
-                super::vello::text::VelloParagraph::new(data, text, width, max_height, options)
+                super::vello::text::VelloParagraph::new(data, spans, width, max_height, options)
+            )),
+            #[cfg(feature = "r-tiny-skia")]
+            RendererData::TinySkia(data) => Self::TinySkia(Rc::new(
+                super::tiny_skia::text::TinySkiaParagraph::new(data, spans, width, max_height, options)
             )),
             _ => panic!()
         }
     }
+
+    /// Lays out a deserialized paragraph with the given renderer data. Other paragraphs are returned unchanged.
+    #[cfg(feature = "serde")]
+    pub fn resolve(&self, renderer_data: &RendererData) -> Self {
+        match self {
+            Self::Unresolved(paragraph) => Self::new_rich(
+                renderer_data, paragraph.spans.clone(), paragraph.width.ceil() as u32, paragraph.max_height, paragraph.options.clone()
+            ),
+            other => other.clone()
+        }
+    }
+}
+
+/// Paragraphs are equal if they share the same layout, i.e. one is a clone of the other
+impl PartialEq for Paragraph {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            #[cfg(feature = "r-wgpu")]
+            (Self::Wgpu, Self::Wgpu) => true,
+            #[cfg(feature = "r-vello")]
+            (Self::Vello(a), Self::Vello(b)) => Rc::ptr_eq(a, b),
+            #[cfg(feature = "r-skia")]
+            (Self::Skia(a), Self::Skia(b)) => Rc::ptr_eq(a, b),
+            #[cfg(feature = "r-tiny-skia")]
+            (Self::TinySkia(a), Self::TinySkia(b)) => Rc::ptr_eq(a, b),
+            #[cfg(feature = "serde")]
+            (Self::Unresolved(a), Self::Unresolved(b)) => Rc::ptr_eq(a, b),
+            _ => false
+        }
+    }
 }
 
 #[enum_dispatch]
 pub trait ParagraphTrait {
     fn options(&self) -> &TextOptions;
+    /// The text of all spans together
+    fn text(&self) -> &str;
+    fn spans(&self) -> &[TextSpan];
+    fn max_height(&self) -> Option<NonZeroU32>;
+    /// The width the paragraph was laid out with
+    fn width(&self) -> f32;
     fn height(&self) -> f32;
 }
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextOptions {
     pub size: f32,
     pub font: Option<String>,
-    pub color: u32,
+    /// Coordinates of gradients and patterns are relative to the paragraph
+    pub brush: Brush,
     /// Only works with variable fonts supporting the 'wght' axis
     pub weight: u32,
     /// Only works with variable fonts supporting the 'ital' or the 'slnt' axis
     pub italic: bool,
-    /// WIP
-    pub underline: bool,
+    pub decoration: Option<TextDecoration>,
     pub wrap: TextWrap,
-    pub overflow: TextOverflow
+    /// What happens to the last line if the text doesn't fit into the paragraph's max height or max lines
+    pub overflow: TextOverflow,
+    /// Lines after it are cut off like the ones that don't fit into the max height
+    pub max_lines: Option<NonZeroU32>,
+    pub align: TextAlign,
+    /// Distance between the baselines of two lines as a multiple of the size, or the font's own line height if `None`
+    pub line_height: Option<f32>,
+    /// Extra space after every character
+    pub letter_spacing: f32,
+    /// Extra space after every whitespace character, on top of the letter spacing
+    pub word_spacing: f32,
+    /// Space before the first line
+    pub indent: f32,
+    /// Distance between tab stops, tabs move the text to the next one
+    pub tab_width: f32
+}
+
+impl TextOptions {
+    /// The style of text in a paragraph with these options, which spans can be based on
+    pub fn span_style(&self) -> SpanStyle {
+        SpanStyle {
+            size: self.size,
+            font: self.font.clone(),
+            brush: self.brush.clone(),
+            weight: self.weight,
+            italic: self.italic,
+            decoration: self.decoration.clone()
+        }
+    }
+}
+
+/// The style of a `TextSpan`, usually based on the paragraph's `TextOptions::span_style`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpanStyle {
+    pub size: f32,
+    /// A font alias, or the default font
+    pub font: Option<String>,
+    /// Coordinates of gradients and patterns are relative to the paragraph
+    pub brush: Brush,
+    /// Only works with variable fonts supporting the 'wght' axis
+    pub weight: u32,
+    /// Only works with variable fonts supporting the 'ital' or the 'slnt' axis
+    pub italic: bool,
+    pub decoration: Option<TextDecoration>
+}
+
+impl SpanStyle {
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_font(mut self, font: Option<String>) -> Self {
+        self.font = font;
+        self
+    }
+
+    pub fn with_brush(mut self, brush: impl Into<Brush>) -> Self {
+        self.brush = brush.into();
+        self
+    }
+
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn with_italic(mut self, italic: bool) -> Self {
+        self.italic = italic;
+        self
+    }
+
+    pub fn with_decoration(mut self, decoration: Option<TextDecoration>) -> Self {
+        self.decoration = decoration;
+        self
+    }
+}
+
+/// A run of text in a paragraph, with its own style
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextSpan {
+    pub text: String,
+    pub style: SpanStyle
+}
+
+impl TextSpan {
+    #[inline]
+    pub fn new(text: impl Into<String>, style: SpanStyle) -> Self {
+        Self { text: text.into(), style }
+    }
+}
+
+/// ### Builds a paragraph from spans of differently styled text, like bold or colored words in a sentence.
+/// Spans added with `RichText::text` use the style of the paragraph's options, others are usually based on `RichText::style`.
+#[derive(Debug, Clone)]
+pub struct RichText {
+    options: TextOptions,
+    spans: Vec<TextSpan>
+}
+
+impl RichText {
+    #[inline]
+    pub fn new(options: TextOptions) -> Self {
+        Self { options, spans: Vec::new() }
+    }
+
+    /// The style of the paragraph's options
+    #[inline]
+    pub fn style(&self) -> SpanStyle {
+        self.options.span_style()
+    }
+
+    /// Adds text in the style of the paragraph's options
+    pub fn text(self, text: impl Into<String>) -> Self {
+        let style = self.style();
+        self.span(text, style)
+    }
+
+    pub fn span(mut self, text: impl Into<String>, style: SpanStyle) -> Self {
+        self.spans.push(TextSpan::new(text, style));
+        self
+    }
+
+    #[inline]
+    pub fn spans(&self) -> &[TextSpan] {
+        &self.spans
+    }
+
+    /// Lays out the spans as a paragraph, see `RendererData::create_paragraph`
+    pub fn build(self, renderer_data: &RendererData, width: u32, max_height: Option<u32>) -> Paragraph {
+        renderer_data.create_rich_paragraph(self.spans, width, max_height, self.options)
+    }
 }
 
 #[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextWrap {
     NoWrap,
     #[default]
@@ -62,23 +252,275 @@ pub enum TextWrap {
 }
 
 #[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextOverflow {
+    /// Cuts off the lines that don't fit
     Clip,
+    /// Cuts off the lines that don't fit and ends the last one with "…"
     #[default]
     Elide
 }
 
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+    /// Stretches the spaces of every line except the last one, so the lines fill the whole width
+    Justify
+}
+
+/// ### Lines drawn along text, like links or spell checking squiggles.
+/// Their positions and default thickness come from the font, so they line up with the glyphs.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextDecoration {
+    pub underline: bool,
+    pub strikethrough: bool,
+    /// Drawn at the ascent of the font
+    pub overline: bool,
+    pub style: DecorationStyle,
+    /// The brush of the text if `None`
+    pub brush: Option<Brush>,
+    /// The thickness the font suggests if `None`
+    pub thickness: Option<f32>
+}
+
+impl TextDecoration {
+    #[inline]
+    pub fn underline() -> Self {
+        Self { underline: true, ..Default::default() }
+    }
+
+    #[inline]
+    pub fn strikethrough() -> Self {
+        Self { strikethrough: true, ..Default::default() }
+    }
+
+    #[inline]
+    pub fn overline() -> Self {
+        Self { overline: true, ..Default::default() }
+    }
+
+    pub fn with_underline(mut self, underline: bool) -> Self {
+        self.underline = underline;
+        self
+    }
+
+    pub fn with_strikethrough(mut self, strikethrough: bool) -> Self {
+        self.strikethrough = strikethrough;
+        self
+    }
+
+    pub fn with_overline(mut self, overline: bool) -> Self {
+        self.overline = overline;
+        self
+    }
+
+    pub fn with_style(mut self, style: DecorationStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_brush(mut self, brush: impl Into<Brush>) -> Self {
+        self.brush = Some(brush.into());
+        self
+    }
+
+    pub fn with_thickness(mut self, thickness: f32) -> Self {
+        self.thickness = Some(thickness);
+        self
+    }
+
+    /// The lines for `width` of text starting at `x`, with its baseline at `baseline`.
+    /// Renderers without native decorations draw these like any other objects.
+    pub(crate) fn objects(&self, x: f32, baseline: f32, width: f32, metrics: &FontMetrics, text_brush: &Brush) -> Vec<Object> {
+        if width <= 0.0 { return Vec::new() }
+        let brush = self.brush.as_ref().unwrap_or(text_brush);
+
+        [
+            (self.underline, metrics.underline_offset, metrics.underline_thickness),
+            (self.strikethrough, metrics.strikeout_offset, metrics.strikeout_thickness),
+            (self.overline, -metrics.ascent, metrics.underline_thickness)
+        ].into_iter().filter(|(enabled, ..)| *enabled).filter_map(|(_, offset, thickness)| {
+            let thickness = self.thickness.unwrap_or(thickness);
+            if thickness <= 0.0 { return None }
+            // The offset is the center of the line
+            let y = baseline + offset;
+
+            Some(match self.style {
+                DecorationStyle::Solid => Object::rectangle(x, y - thickness / 2.0, width, thickness, brush.clone(), None),
+                DecorationStyle::Dashed => Object::path(Path::new().move_to(x, y).line_to(x + width, y), 0x00000000)
+                .with_stroke(Stroke::new(thickness, brush.clone()).with_dash(vec![thickness * 3.0, thickness * 2.0], 0.0)),
+                DecorationStyle::Dotted => {
+                    // Round caps on empty dashes aren't drawn by every renderer, so the dots are circles
+                    let radius = thickness / 2.0;
+                    let count = ((width + thickness) / (thickness * 2.0)).floor().max(1.0) as usize;
+
+                    let path = (0..count).fold(Path::new(), |path, index| {
+                        let cx = x + radius + index as f32 * thickness * 2.0;
+                        path.move_to(cx + radius, y).arc(cx, y, radius, radius, 0.0, TAU).close()
+                    });
+
+                    Object::path(path, brush.clone())
+                },
+                DecorationStyle::Wavy => {
+                    let half_wave = (thickness * 2.0).max(1.0);
+                    let mut path = Path::new().move_to(x, y);
+                    let mut start = x;
+                    let mut up = true;
+
+                    while start < x + width {
+                        let end = (start + half_wave).min(x + width);
+                        // The curve reaches half of the way to its control point
+                        let peak = if up { -thickness * 2.0 } else { thickness * 2.0 };
+                        path = path.quad_to((start + end) / 2.0, y + peak, end, y);
+                        start = end;
+                        up = !up;
+                    }
+
+                    Object::path(path, 0x00000000).with_stroke(Stroke::new(thickness, brush.clone()).with_cap(LineCap::Round))
+                }
+            })
+        }).collect()
+    }
+}
+
+impl Mul<f32> for TextDecoration {
+    type Output = Self;
+
+    fn mul(self, with: f32) -> Self::Output {
+        Self {
+            brush: self.brush.map(|brush| brush * with),
+            thickness: self.thickness.map(|thickness| thickness * with),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DecorationStyle {
+    #[default]
+    Solid,
+    Dotted,
+    Dashed,
+    Wavy
+}
+
 impl Default for TextOptions {
     fn default() -> Self {
         Self {
             weight: 500,
             size: 16.0,
             font: None,
-            color: 0xFFFFFFFF,
+            brush: Brush::default(),
             italic: false,
-            underline: false,
+            decoration: None,
             wrap: TextWrap::default(),
-            overflow: TextOverflow::default()
+            overflow: TextOverflow::default(),
+            max_lines: None,
+            align: TextAlign::default(),
+            line_height: None,
+            letter_spacing: 0.0,
+            word_spacing: 0.0,
+            indent: 0.0,
+            tab_width: 64.0
         }
     }
-}
\ No newline at end of file
+}
+
+/// Vertical metrics of a font at a size, the same for every line
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FontMetrics {
+    /// Distance from the baseline to the top of the tallest glyphs
+    pub ascent: f32,
+    /// Distance from the baseline to the bottom of the lowest glyphs, positive if they reach below it
+    pub descent: f32,
+    /// Extra space between lines
+    pub leading: f32,
+    /// Distance between the baselines of two lines, which is `ascent + descent + leading`
+    pub line_height: f32,
+    /// Distance from the baseline to the center of the underline, positive below it
+    pub underline_offset: f32,
+    pub underline_thickness: f32,
+    /// Distance from the baseline to the center of the strikethrough, negative above it
+    pub strikeout_offset: f32,
+    pub strikeout_thickness: f32
+}
+
+/// The size of text, measured with `RendererDataTrait::measure_text`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TextMetrics {
+    /// Width of the longest line
+    pub width: f32,
+    /// Height of all lines together
+    pub height: f32,
+    pub ascent: f32,
+    pub descent: f32,
+    /// Distance from the top of the text to the baseline of its first line
+    pub baseline: f32
+}
+
+/// The text and options of a paragraph, with the size it had when it was serialized
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct UnresolvedParagraph {
+    pub text: String,
+    pub spans: Vec<TextSpan>,
+    pub width: f32,
+    pub height: f32,
+    pub max_height: Option<NonZeroU32>,
+    pub options: TextOptions
+}
+
+#[cfg(feature = "serde")]
+impl ParagraphTrait for Rc<UnresolvedParagraph> {
+    fn options(&self) -> &TextOptions {
+        &self.options
+    }
+
+    fn text(&self) -> &str {
+        &self.text
+    }
+
+    fn spans(&self) -> &[TextSpan] {
+        &self.spans
+    }
+
+    fn max_height(&self) -> Option<NonZeroU32> {
+        self.max_height
+    }
+
+    fn width(&self) -> f32 {
+        self.width
+    }
+
+    fn height(&self) -> f32 {
+        self.height
+    }
+}
+
+/// Paragraphs are serialized as their text and options, as the layout depends on the renderer
+#[cfg(feature = "serde")]
+impl serde::Serialize for Paragraph {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        UnresolvedParagraph {
+            text: self.text().to_string(),
+            spans: self.spans().to_vec(),
+            width: self.width(),
+            height: self.height(),
+            max_height: self.max_height(),
+            options: self.options().clone()
+        }.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Paragraph {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        UnresolvedParagraph::deserialize(deserializer).map(|paragraph| Self::Unresolved(Rc::new(paragraph)))
+    }
+}
@@ -0,0 +1,656 @@
+use std::{f32::consts::TAU, rc::Rc};
+
+use log::warn;
+use resvg::usvg;
+use tiny_skia::{BlendMode, Color, FillRule, FilterQuality, IntRect, LinearGradient, Mask, Paint, PathBuilder, Pattern, Pixmap, PixmapPaint, Point, RadialGradient, Shader, SpreadMode, StrokeDash, Transform};
+
+use crate::{backend::renderer_data::tiny_skia::TinySkiaRendererData, renderer::{blur, brushes::{Brush, Extend, Gradient, GradientKind}, images::{CacheableImage, PixelFormat}, layout, objects::{self, Rounding}, paths::{self, Path, PathElement}, strokes::{LineCap, LineJoin, Stroke}, svgs::CacheableSvg, transforms}, types::{ClipShape, Dimensions, Object, Shadow, WindowId}};
+
+use super::text::{fill_glyphs, TinySkiaParagraph};
+
+
+/// Approximation of a quarter circle with a cubic bezier
+const KAPPA: f32 = 0.552_284_8;
+
+pub(crate) fn draw_object(data: &TinySkiaRendererData, pixmap: &mut Pixmap, object: &Object, scale: f32, _window_id: Option<&WindowId>) {
+    draw_transformed(data, pixmap, object, Transform::from_scale(scale, scale), None);
+}
+
+/// Like `draw_object`, but only draws where `mask` is set
+pub(crate) fn draw_object_masked(data: &TinySkiaRendererData, pixmap: &mut Pixmap, object: &Object, scale: f32, mask: &Mask) {
+    draw_transformed(data, pixmap, object, Transform::from_scale(scale, scale), Some(mask));
+}
+
+/// Measures a single line of text as (width, height), or `None` if there is no such font
+pub(crate) fn measure_text(data: &TinySkiaRendererData, text: &str, font: &Option<String>, size: f32) -> Option<(f32, f32)> {
+    let font = data.get_font(font)?;
+    let layout = layout::layout_text(&font.font_ref, text, &layout::text_object_options(size));
+    Some((layout.dimensions.width, layout.dimensions.height))
+}
+
+/// The size of the SVG, as it is fitted into the rect of `Object::Svg`
+pub(crate) fn svg_size(data: &TinySkiaRendererData, svg: &CacheableSvg) -> Option<(f32, f32)> {
+    let size = data.get_or_load_svg(svg).size();
+    Some((size.width(), size.height()))
+}
+
+/// The logical bounds of the object, measuring text with the same layout it is drawn with
+pub(crate) fn object_bounds(data: &TinySkiaRendererData, object: &Object) -> Option<objects::Rect> {
+    object.bounds_with(&|text, font, size| measure_text(data, text, font, size))
+}
+
+/// Draws the object with `transform` mapping its coordinates to physical pixels, only where `mask` is set
+pub(crate) fn draw_transformed(data: &TinySkiaRendererData, pixmap: &mut Pixmap, object: &Object, transform: Transform, mask: Option<&Mask>) {
+    match object {
+        Object::Rectangle { rounding, brush, rect, stroke, shadow } => {
+            if let Some(shadow) = shadow {
+                draw_shadow(pixmap, rect, rounding.as_ref(), shadow, transform, mask);
+            }
+
+            let path = match rounding {
+                Some(r) => rounded_rect_path(rect, r),
+                None => skia_rect(rect).map(PathBuilder::from_rect)
+            };
+            let Some(path) = path else { return };
+
+            let source = BrushSource::new(data, brush, pixmap, Some(path.bounds()), transform);
+            pixmap.fill_path(&path, &source.paint(), FillRule::Winding, transform, mask);
+
+            if let Some(stroke) = stroke {
+                stroke_path(data, pixmap, &path, stroke, transform, mask);
+            }
+        },
+        Object::Text { text, font, size, brush, position, decoration } => {
+            let font = data.get_font(font).expect("No Font available!");
+
+            let options = layout::text_object_options(*size);
+            let layout = layout::layout_text(&font.font_ref, text, &options);
+            // Glyphs can reach outside of their lines, like in `Object::bounds`
+            let bounds = tiny_skia::Rect::from_xywh(position.x, position.y, layout.dimensions.width, layout.dimensions.height)
+            .and_then(|bounds| bounds.outset(size / 2.0, size / 2.0));
+            let source = BrushSource::new(data, brush, pixmap, bounds, transform);
+
+            fill_glyphs(
+                pixmap,
+                &font,
+                &layout,
+                &source.paint(),
+                // The brush is in object coordinates, so only the glyphs are translated
+                transform.pre_translate(position.x, position.y),
+                transform,
+                mask
+            );
+
+            if let Some(decoration) = decoration {
+                for line in layout::text_decorations(&font.font_ref, text, &options, position.x, position.y, decoration, brush) {
+                    draw_transformed(data, pixmap, &line, transform, mask);
+                }
+            }
+        },
+        Object::Paragraph { paragraph, position } => {
+            // Deserialized paragraphs have to be resolved with this renderer's data first
+            let paragraph: Result<Rc<TinySkiaParagraph>, _> = paragraph.clone().try_into();
+            let Ok(paragraph) = paragraph else {
+                warn!("Skipping a paragraph that wasn't laid out for tiny-skia, resolve it with `Paragraph::resolve` first");
+                return
+            };
+
+            paragraph.draw(
+                data,
+                pixmap,
+                transform.pre_translate(position.x, position.y),
+                mask
+            );
+        },
+        Object::Image { image, rect, source, fit } => {
+            let image_pixmap = data.get_or_load_image(image);
+            let Dimensions { width, height } = *image.dimensions();
+
+            let source = source.unwrap_or(Object::rect(0.0, 0.0, width as f32, height as f32));
+            let (dest, visible) = fit.place((source.width, source.height), rect);
+
+            draw_image_part(pixmap, &image_pixmap, &source, &dest, &visible, transform, mask);
+        },
+        Object::NinePatch { image, rect, insets, scale } => {
+            let image_pixmap = data.get_or_load_image(image);
+
+            for (source, dest) in insets.patches(*image.dimensions(), rect, *scale) {
+                draw_image_part(pixmap, &image_pixmap, &source, &dest, &dest, transform, mask);
+            }
+        },
+        Object::Svg { svg, brush, rect, fit } => {
+            let tree = data.get_or_load_svg(svg);
+            let size = tree.size();
+
+            let (objects::Rect { x, y, width, height }, visible) = fit.place((size.width(), size.height()), rect);
+            let Some(dest) = skia_rect(&visible) else { return };
+
+            // Rasterize into a pixmap covering the visible part of the transformed rect, so it can be tinted on its own
+            let Some(bounds) = PathBuilder::from_rect(dest)
+            .transform(transform)
+            .and_then(|path| path.bounds().round_out())
+            .and_then(|bounds| bounds.intersect(&IntRect::from_xywh(0, 0, pixmap.width(), pixmap.height())?))
+            else { return };
+            let Some(mut svg_pixmap) = Pixmap::new(bounds.width(), bounds.height()) else { return };
+
+            let local_transform = Transform::from_translate(-bounds.x() as f32, -bounds.y() as f32).pre_concat(transform);
+
+            resvg::render(
+                &tree,
+                local_transform
+                .pre_translate(x, y)
+                .pre_scale(width / size.width(), height / size.height()),
+                &mut svg_pixmap.as_mut()
+            );
+
+            // The bounds only match the visible rect if the transform keeps it axis aligned, so cut off the rest
+            if let Some(mut visible) = Mask::new(bounds.width(), bounds.height()) {
+                visible.fill_path(&PathBuilder::from_rect(dest), FillRule::Winding, true, local_transform);
+                svg_pixmap.apply_mask(&visible);
+            }
+
+            // Tint the SVG, keeping its alpha
+            let source = BrushSource::new(data, brush, &svg_pixmap, None, local_transform);
+            let mut tint = source.paint();
+            tint.blend_mode = BlendMode::SourceIn;
+
+            svg_pixmap.fill_rect(dest, &tint, local_transform, None);
+
+            pixmap.draw_pixmap(
+                bounds.x(),
+                bounds.y(),
+                svg_pixmap.as_ref(),
+                &PixmapPaint::default(),
+                Transform::identity(),
+                mask
+            );
+        },
+        Object::Path { path, brush, stroke } => {
+            let Some(skia_path) = skia_path(path) else { return };
+
+            let source = BrushSource::new(data, brush, pixmap, Some(skia_path.bounds()), transform);
+            pixmap.fill_path(&skia_path, &source.paint(), fill_rule(path.fill_rule), transform, mask);
+
+            if let Some(stroke) = stroke {
+                stroke_path(data, pixmap, &skia_path, stroke, transform, mask);
+            }
+        },
+        Object::Group { transform: group_transform, children } => {
+            let transform = transform.pre_concat(skia_transform(group_transform));
+
+            for child in children {
+                draw_transformed(data, pixmap, child, transform, mask);
+            }
+        },
+        Object::Clip { shape, children } => {
+            let (path, fill_rule) = match shape {
+                ClipShape::Rect(rect) => (skia_rect(rect).map(PathBuilder::from_rect), FillRule::Winding),
+                ClipShape::RoundedRect(rect, rounding) => (rounded_rect_path(rect, rounding), FillRule::Winding),
+                ClipShape::Path(path) => (skia_path(path), self::fill_rule(path.fill_rule))
+            };
+            // Nothing is visible inside of an empty clip
+            let Some(path) = path else { return };
+
+            // Nested clips intersect with the outer mask
+            let clip_mask = match mask {
+                Some(mask) => {
+                    let mut clip_mask = mask.clone();
+                    clip_mask.intersect_path(&path, fill_rule, true, transform);
+                    clip_mask
+                },
+                None => {
+                    let Some(mut clip_mask) = Mask::new(pixmap.width(), pixmap.height()) else { return };
+                    clip_mask.fill_path(&path, fill_rule, true, transform);
+                    clip_mask
+                }
+            };
+
+            for child in children {
+                draw_transformed(data, pixmap, child, transform, Some(&clip_mask));
+            }
+        },
+        Object::Layer { opacity, blend, children } => {
+            let Some(mut layer) = Pixmap::new(pixmap.width(), pixmap.height()) else { return };
+
+            for child in children {
+                draw_transformed(data, &mut layer, child, transform, mask);
+            }
+
+            pixmap.draw_pixmap(
+                0,
+                0,
+                layer.as_ref(),
+                &PixmapPaint {
+                    opacity: *opacity,
+                    blend_mode: skia_blend_mode(*blend),
+                    quality: FilterQuality::Nearest
+                },
+                Transform::identity(),
+                None
+            );
+        },
+        Object::Tagged { object, .. } => draw_transformed(data, pixmap, object, transform, mask),
+        Object::BackdropBlur { rect, rounding, blur, tint } => {
+            let path = match rounding {
+                Some(r) => rounded_rect_path(rect, r),
+                None => skia_rect(rect).map(PathBuilder::from_rect)
+            };
+            let Some(path) = path else { return };
+
+            draw_backdrop_blur(pixmap, &path, *blur, transform, mask);
+
+            if let Some(tint) = tint {
+                let source = BrushSource::new(data, tint, pixmap, Some(path.bounds()), transform);
+                pixmap.fill_path(&path, &source.paint(), FillRule::Winding, transform, mask);
+            }
+        }
+    }
+}
+
+/// Replaces what is inside of the path with a blurred version of itself
+fn draw_backdrop_blur(pixmap: &mut Pixmap, path: &tiny_skia::Path, blur: f32, transform: Transform, mask: Option<&Mask>) {
+    let sigma = blur * (transform.sx * transform.sy - transform.kx * transform.ky).abs().sqrt();
+    if sigma <= 0.0 { return }
+    let outset = (sigma * 3.0).ceil();
+
+    // Pixels around the path are needed as well, so they can be blurred into it
+    let Some(bounds) = path.clone()
+    .transform(transform)
+    .and_then(|path| path.bounds().outset(outset, outset))
+    .and_then(|bounds| bounds.round_out())
+    .and_then(|bounds| bounds.intersect(&IntRect::from_xywh(0, 0, pixmap.width(), pixmap.height())?))
+    else { return };
+
+    let Some(original) = pixmap.clone_rect(bounds) else { return };
+    let mut blurred = original.clone();
+    blur::gaussian_blur(blurred.data_mut(), bounds.width() as _, bounds.height() as _, 4, sigma);
+
+    let Some(mut coverage) = Mask::new(bounds.width(), bounds.height()) else { return };
+    coverage.fill_path(
+        path,
+        FillRule::Winding,
+        true,
+        Transform::from_translate(-bounds.x() as f32, -bounds.y() as f32).pre_concat(transform)
+    );
+
+    if let Some(mask) = mask {
+        let (mask_width, offset) = (mask.width() as usize, bounds.y() as usize * mask.width() as usize + bounds.x() as usize);
+
+        for (index, value) in coverage.data_mut().iter_mut().enumerate() {
+            let (x, y) = (index % bounds.width() as usize, index / bounds.width() as usize);
+            *value = (*value as u32 * mask.data()[offset + y * mask_width + x] as u32 / 255) as u8;
+        }
+    }
+
+    // The mask of tiny-skia scales the source instead of blending with the destination,
+    // so the blurred pixels are blended with the original ones here and copied back as a whole
+    for ((blurred, original), &coverage) in blurred.data_mut().chunks_exact_mut(4)
+    .zip(original.data().chunks_exact(4))
+    .zip(coverage.data()) {
+        let coverage = coverage as u32;
+
+        for (blurred, &original) in blurred.iter_mut().zip(original) {
+            *blurred = ((*blurred as u32 * coverage + original as u32 * (255 - coverage) + 127) / 255) as u8;
+        }
+    }
+
+    pixmap.draw_pixmap(
+        bounds.x(),
+        bounds.y(),
+        blurred.as_ref(),
+        &PixmapPaint {
+            blend_mode: BlendMode::Source,
+            ..Default::default()
+        },
+        Transform::identity(),
+        None
+    );
+}
+
+/// Fills `visible` with the `source` part of the image, stretched to `dest`.
+fn draw_image_part(pixmap: &mut Pixmap, image: &Pixmap, source: &objects::Rect, dest: &objects::Rect, visible: &objects::Rect, transform: Transform, mask: Option<&Mask>) {
+    let objects::Rect { x: src_x, y: src_y, width: src_width, height: src_height } = *source;
+    let objects::Rect { x: dst_x, y: dst_y, width: dst_width, height: dst_height } = *dest;
+    if source.is_empty() { return }
+    let Some(visible) = skia_rect(visible) else { return };
+
+    let (scale_x, scale_y) = (dst_width / src_width, dst_height / src_height);
+
+    let paint = Paint {
+        shader: Pattern::new(
+            image.as_ref(),
+            SpreadMode::Pad,
+            FilterQuality::Bilinear,
+            1.0,
+            Transform::from_row(scale_x, 0.0, 0.0, scale_y, dst_x - src_x * scale_x, dst_y - src_y * scale_y)
+        ),
+        ..Default::default()
+    };
+
+    pixmap.fill_rect(visible, &paint, transform, mask);
+}
+
+fn draw_shadow(pixmap: &mut Pixmap, rect: &objects::Rect, rounding: Option<&Rounding>, shadow: &Shadow, transform: Transform, mask: Option<&Mask>) {
+    let (xywh, radii) = shadow.shape(rect, rounding);
+    let Some(path) = rounded_rect_path_xywh(xywh, radii) else { return };
+
+    // The blur radius is scaled like the shadow, using the average scale of the transform
+    let sigma = shadow.blur / 2.0 * (transform.sx * transform.sy - transform.kx * transform.ky).abs().sqrt();
+    let outset = (sigma * 3.0).ceil();
+
+    // Only blur the area the shadow can reach, which is visible
+    let Some(bounds) = path.clone()
+    .transform(transform)
+    .and_then(|path| path.bounds().outset(outset, outset))
+    .and_then(|bounds| bounds.round_out())
+    .and_then(|bounds| bounds.intersect(&IntRect::from_xywh(0, 0, pixmap.width(), pixmap.height())?))
+    else { return };
+
+    let Some(mut shadow_mask) = Mask::new(bounds.width(), bounds.height()) else { return };
+    shadow_mask.fill_path(
+        &path,
+        FillRule::Winding,
+        true,
+        Transform::from_translate(-bounds.x() as f32, -bounds.y() as f32).pre_concat(transform)
+    );
+    blur::gaussian_blur(shadow_mask.data_mut(), bounds.width() as _, bounds.height() as _, 1, sigma);
+
+    let Some(mut shadow_pixmap) = Pixmap::new(bounds.width(), bounds.height()) else { return };
+    shadow_pixmap.fill_rect(
+        tiny_skia::Rect::from_xywh(0.0, 0.0, bounds.width() as f32, bounds.height() as f32).unwrap(),
+        &Paint {
+            shader: Shader::SolidColor(color_from_rgba(shadow.color)),
+            ..Default::default()
+        },
+        Transform::identity(),
+        Some(&shadow_mask)
+    );
+
+    pixmap.draw_pixmap(
+        bounds.x(),
+        bounds.y(),
+        shadow_pixmap.as_ref(),
+        &PixmapPaint::default(),
+        Transform::identity(),
+        mask
+    );
+}
+
+pub(crate) fn stroke_path(data: &TinySkiaRendererData, pixmap: &mut Pixmap, path: &tiny_skia::Path, stroke: &Stroke, transform: Transform, mask: Option<&Mask>) {
+    let skia_stroke = tiny_skia::Stroke {
+        width: stroke.width,
+        miter_limit: stroke.miter_limit,
+        line_cap: match stroke.cap {
+            LineCap::Butt => tiny_skia::LineCap::Butt,
+            LineCap::Round => tiny_skia::LineCap::Round,
+            LineCap::Square => tiny_skia::LineCap::Square
+        },
+        line_join: match stroke.join {
+            LineJoin::Miter => tiny_skia::LineJoin::Miter,
+            LineJoin::Round => tiny_skia::LineJoin::Round,
+            LineJoin::Bevel => tiny_skia::LineJoin::Bevel
+        },
+        dash: stroke.dash_pattern().and_then(|dash| StrokeDash::new(dash, stroke.dash_offset))
+    };
+
+    let source = BrushSource::new(data, &stroke.brush, pixmap, path.bounds().outset(stroke.outset(), stroke.outset()), transform);
+    pixmap.stroke_path(path, &source.paint(), &skia_stroke, transform, mask);
+}
+
+/// Owns what a `Paint` for a brush borrows, like the pixmap of an image pattern.
+pub(crate) enum BrushSource {
+    Shader(Shader<'static>),
+    Pattern { pixmap: Rc<Pixmap>, spread_mode: SpreadMode, transform: Transform }
+}
+
+impl BrushSource {
+    /// `pixmap` and `transform` are those of the draw call, needed for brushes that are rasterized up front.
+    /// Those only cover `bounds`, the area that is drawn to in object coordinates, or the whole pixmap if it is `None`.
+    pub(crate) fn new(data: &TinySkiaRendererData, brush: &Brush, pixmap: &Pixmap, bounds: Option<tiny_skia::Rect>, transform: Transform) -> Self {
+        match brush {
+            Brush::Solid(color) => Self::Shader(Shader::SolidColor(color_from_rgba(*color))),
+            Brush::Gradient(gradient) => {
+                let stops: Vec<tiny_skia::GradientStop> = gradient.stops
+                .iter()
+                .map(|stop| tiny_skia::GradientStop::new(stop.offset, color_from_rgba(stop.color)))
+                .collect();
+                let spread_mode = match gradient.extend {
+                    Extend::Pad => SpreadMode::Pad,
+                    Extend::Repeat => SpreadMode::Repeat,
+                    Extend::Reflect => SpreadMode::Reflect
+                };
+
+                let shader = match gradient.kind {
+                    GradientKind::Linear { start, end } => LinearGradient::new(
+                        Point::from_xy(start.0, start.1),
+                        Point::from_xy(end.0, end.1),
+                        stops,
+                        spread_mode,
+                        Transform::identity()
+                    ),
+                    GradientKind::Radial { center, radius } => RadialGradient::new(
+                        Point::from_xy(center.0, center.1),
+                        Point::from_xy(center.0, center.1),
+                        radius,
+                        stops,
+                        spread_mode,
+                        Transform::identity()
+                    ),
+                    GradientKind::Sweep { center, start_angle, end_angle } => {
+                        // tiny-skia has no sweep gradients, so rasterize the part of it that is drawn in device space
+                        let target = tiny_skia::Rect::from_xywh(0.0, 0.0, pixmap.width() as f32, pixmap.height() as f32);
+                        let area = match bounds {
+                            Some(bounds) => bounds.transform(transform).zip(target).and_then(|(bounds, target)| bounds.intersect(&target)),
+                            None => target
+                        }.and_then(|area| area.round_out());
+
+                        let sweep = area.and_then(|area| sweep_pixmap(gradient, center, start_angle, end_angle, area, transform).map(|sweep| (area, sweep)));
+                        let Some((area, sweep)) = sweep else {
+                            return Self::Shader(Shader::SolidColor(Color::TRANSPARENT));
+                        };
+
+                        return Self::Pattern {
+                            pixmap: Rc::new(sweep),
+                            spread_mode: SpreadMode::Pad,
+                            transform: transform.invert().unwrap_or_default().pre_translate(area.x() as f32, area.y() as f32)
+                        };
+                    }
+                };
+
+                Self::Shader(shader.unwrap_or(Shader::SolidColor(Color::TRANSPARENT)))
+            },
+            Brush::Image(pattern) => Self::Pattern {
+                pixmap: data.get_or_load_image(&pattern.image),
+                spread_mode: SpreadMode::Repeat,
+                transform: Transform::from_translate(pattern.x, pattern.y).pre_scale(pattern.scale, pattern.scale)
+            }
+        }
+    }
+
+    pub(crate) fn paint(&self) -> Paint<'_> {
+        let shader = match self {
+            Self::Shader(shader) => shader.clone(),
+            Self::Pattern { pixmap, spread_mode, transform } => Pattern::new(
+                Pixmap::as_ref(pixmap),
+                *spread_mode,
+                FilterQuality::Bilinear,
+                1.0,
+                *transform
+            )
+        };
+
+        Paint {
+            shader,
+            anti_alias: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Rasterizes the device space `area` of the sweep gradient, which is drawn with `transform`
+fn sweep_pixmap(gradient: &Gradient, center: (f32, f32), start_angle: f32, end_angle: f32, area: IntRect, transform: Transform) -> Option<Pixmap> {
+    let inverse = transform.invert()?;
+    let mut pixmap = Pixmap::new(area.width(), area.height())?;
+    let span = end_angle - start_angle;
+    let width = area.width();
+
+    for (index, pixel) in pixmap.pixels_mut().iter_mut().enumerate() {
+        let x = area.x() as f32 + (index as u32 % width) as f32 + 0.5;
+        let y = area.y() as f32 + (index as u32 / width) as f32 + 0.5;
+        let mut point = [Point::from_xy(x, y)];
+        inverse.map_points(&mut point);
+
+        let angle = (point[0].y - center.1).atan2(point[0].x - center.0).rem_euclid(TAU);
+        let offset = if span == 0.0 { 0.0 } else { (angle - start_angle) / span };
+
+        *pixel = gradient_color(gradient, offset).premultiply().to_color_u8();
+    }
+
+    Some(pixmap)
+}
+
+/// Interpolates the color of the gradient at the given offset, applying its extend mode
+fn gradient_color(gradient: &Gradient, offset: f32) -> Color {
+    let offset = match gradient.extend {
+        Extend::Pad => offset.clamp(0.0, 1.0),
+        Extend::Repeat => offset.rem_euclid(1.0),
+        Extend::Reflect => {
+            let offset = offset.rem_euclid(2.0);
+            if offset > 1.0 { 2.0 - offset } else { offset }
+        }
+    };
+
+    let stops = &gradient.stops;
+    let Some(first) = stops.first() else { return Color::TRANSPARENT };
+
+    if offset <= first.offset { return color_from_rgba(first.color) }
+
+    for pair in stops.windows(2) {
+        let (from, to) = (&pair[0], &pair[1]);
+        if offset > to.offset { continue }
+
+        let (from_color, to_color) = (color_from_rgba(from.color), color_from_rgba(to.color));
+        let t = if to.offset > from.offset { (offset - from.offset) / (to.offset - from.offset) } else { 1.0 };
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+
+        return Color::from_rgba(
+            lerp(from_color.red(), to_color.red()),
+            lerp(from_color.green(), to_color.green()),
+            lerp(from_color.blue(), to_color.blue()),
+            lerp(from_color.alpha(), to_color.alpha())
+        ).unwrap_or(Color::TRANSPARENT);
+    }
+
+    color_from_rgba(stops[stops.len() - 1].color)
+}
+
+pub(crate) fn color_from_rgba(rgba: u32) -> Color {
+    let (r, g, b, a) = (rgba >> 24, rgba >> 16, rgba >> 8, rgba);
+    Color::from_rgba8(r as u8, g as u8, b as u8, a as u8)
+}
+
+pub(crate) fn skia_blend_mode(blend: objects::BlendMode) -> BlendMode {
+    match blend {
+        objects::BlendMode::Normal => BlendMode::SourceOver,
+        objects::BlendMode::Multiply => BlendMode::Multiply,
+        objects::BlendMode::Screen => BlendMode::Screen,
+        objects::BlendMode::Overlay => BlendMode::Overlay,
+        objects::BlendMode::Darken => BlendMode::Darken,
+        objects::BlendMode::Lighten => BlendMode::Lighten,
+        objects::BlendMode::ColorDodge => BlendMode::ColorDodge,
+        objects::BlendMode::ColorBurn => BlendMode::ColorBurn,
+        objects::BlendMode::HardLight => BlendMode::HardLight,
+        objects::BlendMode::SoftLight => BlendMode::SoftLight,
+        objects::BlendMode::Difference => BlendMode::Difference,
+        objects::BlendMode::Exclusion => BlendMode::Exclusion,
+        objects::BlendMode::Hue => BlendMode::Hue,
+        objects::BlendMode::Saturation => BlendMode::Saturation,
+        objects::BlendMode::Color => BlendMode::Color,
+        objects::BlendMode::Luminosity => BlendMode::Luminosity
+    }
+}
+
+pub(crate) fn skia_transform(transform: &transforms::Transform) -> Transform {
+    let transforms::Transform { sx, ky, kx, sy, tx, ty } = *transform;
+    Transform::from_row(sx, ky, kx, sy, tx, ty)
+}
+
+pub(crate) fn skia_rect(rect: &objects::Rect) -> Option<tiny_skia::Rect> {
+    tiny_skia::Rect::from_xywh(rect.x, rect.y, rect.width, rect.height)
+}
+
+pub(crate) fn rounded_rect_path(rect: &objects::Rect, rounding: &Rounding) -> Option<tiny_skia::Path> {
+    rounded_rect_path_xywh(
+        (rect.x, rect.y, rect.width, rect.height),
+        [rounding.top_l, rounding.top_r, rounding.bottom_r, rounding.bottom_l]
+    )
+}
+
+/// Radii go clockwise, starting at the top left corner
+fn rounded_rect_path_xywh((x, y, w, h): (f32, f32, f32, f32), radii: [f32; 4]) -> Option<tiny_skia::Path> {
+    let max = w.min(h) / 2.0;
+    let [tl, tr, br, bl] = radii.map(|radius| radius.min(max));
+
+    let mut builder = PathBuilder::new();
+    builder.move_to(x + tl, y);
+    builder.line_to(x + w - tr, y);
+    builder.cubic_to(x + w - tr * (1.0 - KAPPA), y, x + w, y + tr * (1.0 - KAPPA), x + w, y + tr);
+    builder.line_to(x + w, y + h - br);
+    builder.cubic_to(x + w, y + h - br * (1.0 - KAPPA), x + w - br * (1.0 - KAPPA), y + h, x + w - br, y + h);
+    builder.line_to(x + bl, y + h);
+    builder.cubic_to(x + bl * (1.0 - KAPPA), y + h, x, y + h - bl * (1.0 - KAPPA), x, y + h - bl);
+    builder.line_to(x, y + tl);
+    builder.cubic_to(x, y + tl * (1.0 - KAPPA), x + tl * (1.0 - KAPPA), y, x + tl, y);
+    builder.close();
+
+    builder.finish()
+}
+
+pub(crate) fn skia_path(path: &Path) -> Option<tiny_skia::Path> {
+    let mut builder = PathBuilder::new();
+
+    for element in path.elements() {
+        match element {
+            PathElement::MoveTo(x, y) => builder.move_to(x, y),
+            PathElement::LineTo(x, y) => builder.line_to(x, y),
+            PathElement::QuadTo(cx, cy, x, y) => builder.quad_to(cx, cy, x, y),
+            PathElement::CubicTo(cx1, cy1, cx2, cy2, x, y) => builder.cubic_to(cx1, cy1, cx2, cy2, x, y),
+            PathElement::Close => builder.close()
+        }
+    }
+
+    builder.finish()
+}
+
+pub(crate) fn fill_rule(fill_rule: paths::FillRule) -> FillRule {
+    match fill_rule {
+        paths::FillRule::NonZero => FillRule::Winding,
+        paths::FillRule::EvenOdd => FillRule::EvenOdd
+    }
+}
+
+pub(crate) fn image_to_pixmap(image: &CacheableImage) -> Pixmap {
+    let Dimensions { width, height } = *image.dimensions();
+    let pixels = image.pixels();
+
+    let data: Vec<u8> = match image.format() {
+        PixelFormat::RGB8 => pixels
+        .chunks(3)
+        .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+        .collect(),
+        PixelFormat::RGBA8 => pixels
+        .chunks(4)
+        .flat_map(|rgba| {
+            let premultiply = |channel: u8| ((channel as u16 * rgba[3] as u16 + 127) / 255) as u8;
+            [premultiply(rgba[0]), premultiply(rgba[1]), premultiply(rgba[2]), rgba[3]]
+        })
+        .collect(),
+        PixelFormat::RGBA8Premul => pixels.to_vec()
+    };
+
+    Pixmap::from_vec(data, tiny_skia::IntSize::from_wh(width, height).unwrap()).unwrap()
+}
+
+pub(crate) fn svg_to_tree(svg: &CacheableSvg) -> usvg::Tree {
+    usvg::Tree::from_data(&svg.bytes(), &usvg::Options::default()).unwrap()
+}
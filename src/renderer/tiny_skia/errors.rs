@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+
+
+#[derive(Debug, Error)]
+pub enum TinySkiaRendererError {
+    #[error("Failed to create software surface: {0}")]
+    CreateSurface(softbuffer::SoftBufferError),
+    #[error("Failed to present software buffer: {0}")]
+    Present(softbuffer::SoftBufferError),
+    #[error("Failed to allocate a {0}x{1} pixmap")]
+    Pixmap(u32, u32)
+}
@@ -0,0 +1,271 @@
+use std::{cell::{Cell, RefCell}, fmt::Debug, num::NonZeroU32};
+
+use softbuffer::{Context, Surface};
+use tiny_skia::{BlendMode, FillRule, IntRect, Mask, Paint, PathBuilder, Pixmap, Transform};
+
+use crate::{backend::{renderer_data::tiny_skia::TinySkiaRendererData, windowing::window::WindowHandles}, traits::RendererDataTrait, types::{CacheableImage, Damage, Dimensions, Object, PixelFormat, RResult, Rect, RendererData, RendererError, RetainedScene, Window, WindowTrait}};
+
+use self::errors::TinySkiaRendererError;
+
+use super::{overlay::DebugOverlay, RendererTrait, DEFAULT_CLEAR_COLOR};
+
+
+pub mod adapter;
+pub mod errors;
+pub mod text;
+
+
+type SoftSurface = Surface<WindowHandles<'static>, WindowHandles<'static>>;
+
+/// A renderer rasterizing everything on the CPU with `tiny-skia`, presenting through `softbuffer`.
+/// Used as a fallback for machines without a usable GPU.
+/// `softbuffer` can't present transparency, so the window is always opaque.
+pub struct TinySkiaRenderer {
+    surface: RefCell<SoftSurface>,
+    pixmap: RefCell<Pixmap>,
+    clear_color: Cell<u32>,
+    overlay: DebugOverlay
+}
+
+impl TinySkiaRenderer {
+    pub fn new(window: &Window) -> RResult<Self> {
+        let handles = window.handles().or(Err(RendererError::WindowHandles))?;
+        // The surface can not outlive the window, as both are owned by the user, who drops the renderer first.
+        let handles: WindowHandles<'static> = unsafe { std::mem::transmute(handles) };
+
+        let context = Context::new(handles.clone())
+        .map_err(|err| RendererError::TinySkia(TinySkiaRendererError::CreateSurface(err)))?;
+        let surface = Surface::new(&context, handles)
+        .map_err(|err| RendererError::TinySkia(TinySkiaRendererError::CreateSurface(err)))?;
+
+        let Dimensions { width, height } = window.physical_dimensions();
+        let pixmap = new_pixmap(width, height)?;
+
+        Ok(Self {
+            surface: RefCell::new(surface),
+            pixmap: RefCell::new(pixmap),
+            clear_color: Cell::new(DEFAULT_CLEAR_COLOR),
+            overlay: DebugOverlay::new()
+        })
+    }
+}
+
+impl RendererTrait for TinySkiaRenderer {
+    fn set_clear_color(&self, color: u32) {
+        self.clear_color.set(color);
+    }
+
+    fn clear_color(&self) -> u32 {
+        self.clear_color.get()
+    }
+
+    fn set_debug_overlay(&self, enabled: bool) {
+        self.overlay.set_enabled(enabled);
+    }
+
+    fn debug_overlay(&self) -> bool {
+        self.overlay.is_enabled()
+    }
+
+    fn render(&self, window: &Window, data: &RendererData, objects: Vec<&Object>) -> RResult<()> {
+        let Dimensions { width, height } = window.physical_dimensions();
+        // Skip frame if window size is zero (e.g. when minimized)
+        let (Some(non_zero_width), Some(non_zero_height)) = (NonZeroU32::new(width), NonZeroU32::new(height)) else {
+            return Ok(())
+        };
+
+        let mut pixmap = self.pixmap.borrow_mut();
+        if (pixmap.width(), pixmap.height()) != (width, height) {
+            *pixmap = new_pixmap(width, height)?;
+        }
+
+        pixmap.fill(adapter::color_from_rgba(self.clear_color.get()));
+
+        let data = data.try_as_tiny_skia_ref().unwrap();
+        let scale = window.current_scale();
+        let window_id = window.id();
+
+        for object in &objects {
+            adapter::draw_object(data, &mut pixmap, object, scale, Some(&window_id));
+        }
+
+        let overlay = self.overlay.objects(&objects, !data.fonts.borrow().is_empty(), |object| adapter::object_bounds(data, object));
+        for object in &overlay {
+            adapter::draw_object(data, &mut pixmap, object, scale, Some(&window_id));
+        }
+
+        self.present(&pixmap, non_zero_width, non_zero_height, None)
+    }
+
+    /// Only redraws the damaged regions of the pixmap, as it keeps the last frame
+    fn render_scene(&self, window: &Window, data: &RendererData, scene: &mut RetainedScene) -> RResult<()> {
+        let dimensions = window.physical_dimensions();
+        let (scale, clear_color) = (window.current_scale(), self.clear_color.get());
+        let tiny_skia_data = data.try_as_tiny_skia_ref().unwrap();
+
+        // A resized pixmap lost its content
+        let resized = {
+            let pixmap = self.pixmap.borrow();
+            (pixmap.width(), pixmap.height()) != (dimensions.width, dimensions.height)
+        };
+        // The debug overlay changes every frame
+        let damage = if resized || self.overlay.is_enabled() {
+            Damage::Full
+        } else {
+            scene.damage(dimensions, scale, clear_color, |object| adapter::object_bounds(tiny_skia_data, object))
+        };
+
+        match damage {
+            Damage::None => return Ok(()),
+            Damage::Full => self.render(window, data, scene.objects())?,
+            Damage::Regions(regions) => self.render_regions(window, tiny_skia_data, scene.objects(), &regions)?
+        }
+
+        scene.commit(dimensions, scale, clear_color);
+        Ok(())
+    }
+
+    fn recreate(&self, _window: &Window, _data: &RendererData) {
+        // The surface and pixmap are resized lazily in `render`
+    }
+
+    fn transform_data(&self, data: &RendererData) -> Option<RendererData> {
+        match data {
+            RendererData::Placeholder(placeholder) => {
+                let new = TinySkiaRendererData::new();
+
+                let default_index = placeholder.default_index.get() as usize;
+                for (index, (alias, bytes)) in placeholder.fonts.borrow_mut().drain(..).enumerate() {
+                    if index == default_index {
+                        new.register_default_font(&bytes, &alias);
+                    } else {
+                        new.register_font(&bytes, &alias);
+                    }
+                }
+
+                Some(new.into())
+            },
+            _ => None
+        }
+    }
+}
+
+impl TinySkiaRenderer {
+    /// Clears and redraws the regions, which are in physical pixels, then only presents them
+    fn render_regions(&self, window: &Window, data: &TinySkiaRendererData, objects: Vec<&Object>, regions: &[Rect]) -> RResult<()> {
+        let Dimensions { width, height } = window.physical_dimensions();
+        let (Some(non_zero_width), Some(non_zero_height)) = (NonZeroU32::new(width), NonZeroU32::new(height)) else {
+            return Ok(())
+        };
+
+        let regions: Vec<IntRect> = regions.iter()
+        .filter_map(|region| IntRect::from_xywh(region.x as i32, region.y as i32, region.width as u32, region.height as u32))
+        .collect();
+        let Some(mut mask) = Mask::new(width, height) else { return Ok(()) };
+
+        let mut pixmap = self.pixmap.borrow_mut();
+        let mut clear = Paint::default();
+        clear.set_color(adapter::color_from_rgba(self.clear_color.get()));
+        clear.blend_mode = BlendMode::Source;
+        clear.anti_alias = false;
+
+        for region in &regions {
+            let rect = region.to_rect();
+            mask.fill_path(&PathBuilder::from_rect(rect), FillRule::Winding, false, Transform::identity());
+            pixmap.fill_rect(rect, &clear, Transform::identity(), None);
+        }
+
+        let scale = window.current_scale();
+        for object in objects {
+            // Everything outside of the regions would be masked away anyway
+            if let Some(bounds) = adapter::object_bounds(data, object) {
+                let bounds = bounds * scale;
+                if !regions.iter().any(|region| bounds.intersects(&Rect {
+                    x: region.x() as f32, y: region.y() as f32, width: region.width() as f32, height: region.height() as f32
+                })) { continue }
+            }
+
+            adapter::draw_object_masked(data, &mut pixmap, object, scale, &mask);
+        }
+
+        self.present(&pixmap, non_zero_width, non_zero_height, Some(&regions))
+    }
+
+    /// Copies the pixmap to the window. With damage, only those regions are copied and presented,
+    /// if the buffer still holds the last frame.
+    fn present(&self, pixmap: &Pixmap, width: NonZeroU32, height: NonZeroU32, damage: Option<&[IntRect]>) -> RResult<()> {
+        let mut surface = self.surface.borrow_mut();
+        surface.resize(width, height)
+        .map_err(|err| RendererError::TinySkia(TinySkiaRendererError::Present(err)))?;
+
+        let mut buffer = surface.buffer_mut()
+        .map_err(|err| RendererError::TinySkia(TinySkiaRendererError::Present(err)))?;
+
+        // A buffer age of 1 means it holds the last presented frame
+        let damage = damage.filter(|_| buffer.age() == 1);
+        let stride = width.get() as usize;
+
+        match damage {
+            Some(regions) => {
+                for region in regions {
+                    for y in region.top()..region.bottom() {
+                        let row = y as usize * stride;
+                        let range = row + region.left() as usize..row + region.right() as usize;
+                        copy_pixels(&mut buffer[range.clone()], &pixmap.pixels()[range]);
+                    }
+                }
+            },
+            None => copy_pixels(&mut buffer, pixmap.pixels())
+        }
+
+        let presented = match damage {
+            Some(regions) => {
+                let rects: Vec<softbuffer::Rect> = regions.iter().filter_map(|region| Some(softbuffer::Rect {
+                    x: region.x() as u32,
+                    y: region.y() as u32,
+                    width: NonZeroU32::new(region.width())?,
+                    height: NonZeroU32::new(region.height())?
+                })).collect();
+                buffer.present_with_damage(&rects)
+            },
+            None => buffer.present()
+        };
+
+        presented.map_err(|err| RendererError::TinySkia(TinySkiaRendererError::Present(err)))
+    }
+}
+
+/// softbuffer expects 0RGB pixels, tiny-skia stores premultiplied RGBA
+fn copy_pixels(target: &mut [u32], pixels: &[tiny_skia::PremultipliedColorU8]) {
+    for (target, pixel) in target.iter_mut().zip(pixels) {
+        let color = pixel.demultiply();
+        *target = (color.red() as u32) << 16 | (color.green() as u32) << 8 | color.blue() as u32;
+    }
+}
+
+/// Renders the objects into an image, without needing a window.
+pub(crate) fn render_offscreen(data: &TinySkiaRendererData, objects: Vec<&Object>, dimensions: Dimensions<u32>, scale: f32) -> RResult<CacheableImage> {
+    let Dimensions { width, height } = dimensions;
+    if width == 0 || height == 0 { return Err(RendererError::ZeroSize) }
+
+    let mut pixmap = new_pixmap(width, height)?;
+
+    for object in objects {
+        adapter::draw_object(data, &mut pixmap, object, scale, None);
+    }
+
+    Ok(CacheableImage::new(pixmap.take().into(), PixelFormat::RGBA8Premul, dimensions))
+}
+
+fn new_pixmap(width: u32, height: u32) -> RResult<Pixmap> {
+    Pixmap::new(width.max(1), height.max(1))
+    .ok_or(RendererError::TinySkia(TinySkiaRendererError::Pixmap(width, height)))
+}
+
+
+impl Debug for TinySkiaRenderer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TinySkiaRenderer")
+        .finish_non_exhaustive()
+    }
+}
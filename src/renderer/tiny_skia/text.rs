@@ -0,0 +1,142 @@
+use std::{fmt::Debug, num::NonZeroU32, rc::Rc};
+
+use skrifa::{instance::Size, outline::{DrawSettings, OutlinePen}, GlyphId, MetadataProvider};
+use tiny_skia::{FillRule, Mask, Paint, PathBuilder, Pixmap, Transform};
+
+use crate::{backend::renderer_data::tiny_skia::{TinySkiaFont, TinySkiaRendererData}, renderer::layout::{self, GlyphLayout}, types::{Dimensions, Object, ParagraphTrait, TextOptions, TextSpan}};
+
+use super::adapter::BrushSource;
+
+
+pub struct TinySkiaParagraph {
+    options: TextOptions,
+    text: String,
+    spans: Vec<TextSpan>,
+    max_height: Option<NonZeroU32>,
+    /// The font and glyphs of every span
+    runs: Vec<(TinySkiaFont, GlyphLayout)>,
+    decorations: Vec<Object>,
+    dimensions: Dimensions<f32>
+}
+
+impl ParagraphTrait for Rc<TinySkiaParagraph> {
+    fn options(&self) -> &TextOptions {
+        &self.options
+    }
+
+    fn text(&self) -> &str {
+        &self.text
+    }
+
+    fn spans(&self) -> &[TextSpan] {
+        &self.spans
+    }
+
+    fn max_height(&self) -> Option<NonZeroU32> {
+        self.max_height
+    }
+
+    fn width(&self) -> f32 {
+        self.dimensions.width
+    }
+
+    fn height(&self) -> f32 {
+        self.dimensions.height
+    }
+}
+
+impl TinySkiaParagraph {
+    pub fn new(data: &TinySkiaRendererData, spans: Vec<TextSpan>, width: u32, max_height: Option<NonZeroU32>, options: TextOptions) -> Self {
+        let fonts: Vec<_> = spans.iter().map(|span| data.get_font(&span.style.font).expect("No Font available!")).collect();
+        let layout_spans: Vec<_> = fonts.iter().zip(&spans).map(|(font, span)| (&font.font_ref, span)).collect();
+
+        let layout = layout::layout_paragraph(&layout_spans, width, max_height, &options);
+
+        Self {
+            options,
+            text: spans.iter().map(|span| span.text.as_str()).collect(),
+            spans,
+            max_height,
+            runs: fonts.into_iter().zip(layout.runs).collect(),
+            decorations: layout.decorations,
+            dimensions: layout.dimensions
+        }
+    }
+
+    pub(crate) fn draw(&self, data: &TinySkiaRendererData, pixmap: &mut Pixmap, transform: Transform, mask: Option<&Mask>) {
+        for (span, (font, layout)) in self.spans.iter().zip(&self.runs) {
+            // Glyphs can reach outside of the paragraph, like in `Object::bounds`
+            let bounds = tiny_skia::Rect::from_xywh(0.0, 0.0, self.dimensions.width, self.dimensions.height)
+            .and_then(|bounds| bounds.outset(layout.size / 2.0, layout.size / 2.0));
+            let source = BrushSource::new(data, &span.style.brush, pixmap, bounds, transform);
+            fill_glyphs(pixmap, font, layout, &source.paint(), transform, transform, mask);
+        }
+
+        for decoration in &self.decorations {
+            super::adapter::draw_transformed(data, pixmap, decoration, transform, mask);
+        }
+    }
+}
+
+/// Fills the outlines of all laid out glyphs as a single path.
+/// The glyphs are placed with `transform`, while the paint's shader uses `paint_transform`.
+pub(crate) fn fill_glyphs(pixmap: &mut Pixmap, font: &TinySkiaFont, layout: &GlyphLayout, paint: &Paint, transform: Transform, paint_transform: Transform, mask: Option<&Mask>) {
+    let outlines = font.font_ref.outline_glyphs();
+    let mut pen = GlyphPen { builder: PathBuilder::new(), x: 0.0, y: 0.0 };
+
+    for glyph in &layout.glyphs {
+        let Some(outline) = outlines.get(GlyphId::new(glyph.id)) else { continue };
+
+        pen.x = glyph.x;
+        pen.y = glyph.y;
+        outline.draw(DrawSettings::unhinted(Size::new(layout.size), &layout.location), &mut pen).ok();
+    }
+
+    let Some(path) = pen.builder.finish() else { return };
+
+    if transform == paint_transform {
+        pixmap.fill_path(&path, paint, FillRule::Winding, transform, mask);
+    } else if let Some(path) = paint_transform.invert().and_then(|inverse| path.transform(inverse.pre_concat(transform))) {
+        // Move the glyphs into the paint's coordinate space instead
+        pixmap.fill_path(&path, paint, FillRule::Winding, paint_transform, mask);
+    }
+}
+
+/// Collects glyph outlines into a tiny-skia path, converting from the font's y-up coordinates.
+struct GlyphPen {
+    builder: PathBuilder,
+    x: f32,
+    y: f32
+}
+
+impl OutlinePen for GlyphPen {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.builder.move_to(self.x + x, self.y - y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.builder.line_to(self.x + x, self.y - y);
+    }
+
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        self.builder.quad_to(self.x + cx0, self.y - cy0, self.x + x, self.y - y);
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        self.builder.cubic_to(self.x + cx0, self.y - cy0, self.x + cx1, self.y - cy1, self.x + x, self.y - y);
+    }
+
+    fn close(&mut self) {
+        self.builder.close();
+    }
+}
+
+
+impl Debug for TinySkiaParagraph {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TinySkiaParagraph")
+        .field("options", &self.options)
+        .field("text", &self.text)
+        .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,103 @@
+use std::ops::Mul;
+
+
+/// A 2D affine transformation, mapping a point with
+/// ```text
+/// x' = sx * x + kx * y + tx
+/// y' = ky * x + sy * y + ty
+/// ```
+/// Angles are in radians and rotate clockwise, as y points down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transform {
+    pub sx: f32,
+    pub ky: f32,
+    pub kx: f32,
+    pub sy: f32,
+    pub tx: f32,
+    pub ty: f32
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self { sx: 1.0, ky: 0.0, kx: 0.0, sy: 1.0, tx: 0.0, ty: 0.0 };
+
+    #[inline]
+    pub fn translate(x: f32, y: f32) -> Self {
+        Self { tx: x, ty: y, ..Self::IDENTITY }
+    }
+
+    #[inline]
+    pub fn scale(scale: f32) -> Self {
+        Self::scale_non_uniform(scale, scale)
+    }
+
+    #[inline]
+    pub fn scale_non_uniform(x: f32, y: f32) -> Self {
+        Self { sx: x, sy: y, ..Self::IDENTITY }
+    }
+
+    pub fn rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self { sx: cos, ky: sin, kx: -sin, sy: cos, tx: 0.0, ty: 0.0 }
+    }
+
+    /// Rotates around the given point, instead of the origin
+    pub fn rotate_about(angle: f32, x: f32, y: f32) -> Self {
+        Self::translate(x, y) * Self::rotate(angle) * Self::translate(-x, -y)
+    }
+
+    /// Skews along the x and y axis by the given angles
+    pub fn skew(x_angle: f32, y_angle: f32) -> Self {
+        Self { kx: x_angle.tan(), ky: y_angle.tan(), ..Self::IDENTITY }
+    }
+
+    /// Applies `other` after this transform
+    #[inline]
+    pub fn then(self, other: Transform) -> Self {
+        other * self
+    }
+
+    pub fn map_point(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.sx * x + self.kx * y + self.tx,
+            self.ky * x + self.sy * y + self.ty
+        )
+    }
+
+    /// Returns `None` if the transform can't be inverted, for example when scaling by zero.
+    pub fn invert(&self) -> Option<Self> {
+        let determinant = self.sx * self.sy - self.kx * self.ky;
+        if determinant == 0.0 || !determinant.is_finite() { return None }
+
+        let inverse = 1.0 / determinant;
+        let (sx, ky, kx, sy) = (self.sy * inverse, -self.ky * inverse, -self.kx * inverse, self.sx * inverse);
+
+        Some(Self {
+            sx, ky, kx, sy,
+            tx: -(sx * self.tx + kx * self.ty),
+            ty: -(ky * self.tx + sy * self.ty)
+        })
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// `a * b` applies `b` first, then `a`.
+impl Mul for Transform {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        Self {
+            sx: self.sx * other.sx + self.kx * other.ky,
+            ky: self.ky * other.sx + self.sy * other.ky,
+            kx: self.sx * other.kx + self.kx * other.sy,
+            sy: self.ky * other.kx + self.sy * other.sy,
+            tx: self.sx * other.tx + self.kx * other.ty + self.tx,
+            ty: self.ky * other.tx + self.sy * other.ty + self.ty
+        }
+    }
+}
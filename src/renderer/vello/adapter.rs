@@ -1,110 +1,147 @@
 use std::{rc::Rc, sync::Arc};
 
-use skrifa::MetadataProvider;
-use vello::{kurbo::{Affine, RoundedRect, RoundedRectRadii}, peniko::{Color, Fill}, Glyph, Scene};
+use log::warn;
+use vello::{kurbo::{self, Affine, BezPath, Cap, Join, RoundedRect, RoundedRectRadii, Shape}, peniko::{Color, Fill, Mix}, Glyph, Scene};
 
-use crate::{backend::renderer_data::vello::VelloRendererData, types::{Dimensions, Object, Rect, WindowId}};
+use crate::{backend::renderer_data::vello::VelloRendererData, renderer::{blur, brushes::{Brush, Extend, GradientKind}, layout, paths::{FillRule, Path, PathElement}, strokes::{LineCap, LineJoin, Stroke}, transforms::Transform}, types::{BlendMode, CacheableImage, CacheableSvg, ClipShape, Dimensions, Object, RResult, Rect, Rounding, Shadow, WindowId}};
 
-use super::{text::VelloParagraph, VelloRenderer};
+use super::text::VelloParagraph;
 
 
-pub(crate) fn draw_object(_renderer: &VelloRenderer, data: &VelloRendererData, scene: &mut Scene, object: &Object, scale: f32, _window_id: &WindowId) {
+/// Lets objects like `Object::BackdropBlur` read back what has been drawn to the scene so far
+pub(crate) struct Backdrop<'a> {
+    /// Renders the scene and returns its (non-premultiplied) RGBA8 pixels
+    pub read_back: &'a mut dyn FnMut(&Scene) -> RResult<Vec<u8>>,
+    pub dimensions: Dimensions<u32>,
+    /// How many layers are currently pushed, as they have to be popped before the scene can be rendered
+    pub depth: usize
+}
+
+impl<'a> Backdrop<'a> {
+    pub fn new(read_back: &'a mut dyn FnMut(&Scene) -> RResult<Vec<u8>>, dimensions: Dimensions<u32>) -> Self {
+        Self { read_back, dimensions, depth: 0 }
+    }
+}
+
+/// Draws the object into the scene. Without a `backdrop`, backdrop effects are skipped.
+pub(crate) fn draw_object(data: &VelloRendererData, scene: &mut Scene, object: &Object, scale: f32, window_id: Option<&WindowId>, backdrop: Option<&mut Backdrop>) {
+    draw_transformed(data, scene, object, Affine::scale(scale as _), window_id, backdrop);
+}
+
+/// Measures a single line of text as (width, height), or `None` if there is no such font
+pub(crate) fn measure_text(data: &VelloRendererData, text: &str, font: &Option<String>, size: f32) -> Option<(f32, f32)> {
+    let font = data.get_font(font)?;
+    let layout = layout::layout_text(&font.font_ref, text, &layout::text_object_options(size));
+    Some((layout.dimensions.width, layout.dimensions.height))
+}
+
+/// The size of the SVG, as it is fitted into the rect of `Object::Svg`, or `None` if it can't be parsed
+pub(crate) fn svg_size(svg: &CacheableSvg) -> Option<(f32, f32)> {
+    let tree = vello_svg::usvg::Tree::from_data(&svg.bytes(), &vello_svg::usvg::Options::default()).ok()?;
+    Some((tree.size().width(), tree.size().height()))
+}
+
+/// The logical bounds of the object, measuring text with the same layout it is drawn with
+pub(crate) fn object_bounds(data: &VelloRendererData, object: &Object) -> Option<Rect> {
+    object.bounds_with(&|text, font, size| measure_text(data, text, font, size))
+}
+
+/// Draws the object with `transform` mapping its coordinates to physical pixels
+fn draw_transformed(data: &VelloRendererData, scene: &mut Scene, object: &Object, transform: Affine, window_id: Option<&WindowId>, mut backdrop: Option<&mut Backdrop>) {
     match object {
-        Object::Rectangle { rounding, color, rect } => {
-            if let Some(r) = rounding {
-                let rect = RoundedRect::from_rect(
-                    vello_rect(rect),
-                    RoundedRectRadii::new(r.top_l.into(), r.top_r.into(), r.bottom_l.into(), r.bottom_r.into())
-                );
-                let color = color_from_rgba(*color);
+        Object::Rectangle { rounding, brush, rect, stroke, shadow } => {
+            if let Some(shadow) = shadow {
+                draw_shadow(scene, rect, rounding.as_ref(), shadow, transform);
+            }
 
-                scene.fill(
-                    Fill::NonZero,
-                    Affine::scale(scale as _),
-                    color,
-                    None,
-                    &rect
-                );
-            } else {
-                let rect = vello_rect(rect);
-                let color = color_from_rgba(*color);
+            let rect = vello_rounded_rect(rect, rounding.as_ref());
 
-                scene.fill(
-                    Fill::NonZero,
-                    Affine::scale(scale as _),
-                    color,
-                    None,
-                    &rect
-                );
+            scene.fill(
+                Fill::NonZero,
+                transform,
+                &vello_brush(data, brush),
+                brush_transform(brush),
+                &rect
+            );
+
+            if let Some(stroke) = stroke {
+                stroke_shape(data, scene, stroke, transform, &rect);
             }
         },
-        Object::Text { text, font, size, color, position } => {
+        Object::Text { text, font, size, brush, position, decoration } => {
             let font = data.get_font(font).expect("No Font available!");
 
+            let options = layout::text_object_options(*size);
+            let layout = layout::layout_text(&font.font_ref, text, &options);
+            let (x, y) = (position.x, position.y);
 
-            let size = size * scale;
-            
-            let axes = font.font_ref.axes();
-            let var_loc = axes.location::<&[(&str, f32)]>(&[]);
-            let font_size = skrifa::instance::Size::new(size);
-            let charmap = font.font_ref.charmap();
-            let metrics = font.font_ref.metrics(font_size, &var_loc);
-            let line_height = metrics.ascent - metrics.descent + metrics.leading;
-            let glyph_metrics = font.font_ref.glyph_metrics(font_size, &var_loc);
-            let start_x = position.x as f32 * scale;
-            let mut pen_x = start_x;
-            let mut pen_y = position.y as f32 * scale + line_height;
-
-            // For debugging
-            // self::draw_object(_renderer, data, scene, &Object::rectangle(position.x, position.y, 20, line_height as _, 0xEE667788, None), scale, _window_id);
-
-            scene.draw_glyphs(&font.font)
+            let draw_glyphs = |scene: &mut Scene, glyph_brush: &vello::peniko::Brush| scene.draw_glyphs(&font.font)
+            // Hinting is only applied by Vello if the transform is a uniform scale
             .hint(true)
-            .font_size(size)
-            .brush(color_from_rgba(*color))
-            .normalized_coords(bytemuck::cast_slice(var_loc.coords()))
-            .draw(Fill::NonZero, text.chars().filter_map(|ch| {
-                    if ch == '\n' {
-                        pen_y += line_height;
-                        pen_x = start_x;
-                        return None;
-                    }
-                    let gid = charmap.map(ch).unwrap_or_default();
-                    let advance = glyph_metrics.advance_width(gid).unwrap_or_default();
-                    let x = pen_x;
-                    let y = pen_y;
-                    pen_x += advance;
-
-                    Some(Glyph {
-                        id: gid.to_u32(),
-                        x,
-                        y,
-                    })
+            .transform(transform)
+            .font_size(layout.size)
+            .brush(glyph_brush)
+            .normalized_coords(bytemuck::cast_slice(layout.location.coords()))
+            .draw(Fill::NonZero, layout.glyphs.iter().map(|glyph| Glyph {
+                id: glyph.id,
+                x: x + glyph.x,
+                y: y + glyph.y,
             }));
+
+            match brush_transform(brush) {
+                // There is no brush transform for glyph runs, so the pattern is filled over the text and only kept where the glyphs are
+                Some(pattern_transform) => {
+                    // Glyphs can reach outside of their lines, like in `Object::bounds`
+                    let bounds = vello_rect(&Rect { x, y, width: layout.dimensions.width, height: layout.dimensions.height }.outset(size / 2.0));
+
+                    scene.push_layer(vello::peniko::BlendMode::new(Mix::Normal, vello::peniko::Compose::SrcOver), 1.0, transform, &bounds);
+                    draw_glyphs(scene, &vello::peniko::Brush::Solid(Color::WHITE));
+
+                    scene.push_layer(vello::peniko::BlendMode::new(Mix::Normal, vello::peniko::Compose::SrcIn), 1.0, transform, &bounds);
+                    scene.fill(Fill::NonZero, transform, &vello_brush(data, brush), Some(pattern_transform), &bounds);
+                    scene.pop_layer();
+
+                    scene.pop_layer();
+                },
+                None => draw_glyphs(scene, &vello_brush(data, brush))
+            }
+
+            if let Some(decoration) = decoration {
+                for line in layout::text_decorations(&font.font_ref, text, &options, x, y, decoration, brush) {
+                    draw_transformed(data, scene, &line, transform, window_id, backdrop.as_deref_mut());
+                }
+            }
         },
         Object::Paragraph { paragraph, position } => {
-            let paragraph: Rc<VelloParagraph> = paragraph.clone().try_into().unwrap();
+            // Deserialized paragraphs have to be resolved with this renderer's data first
+            let paragraph: Result<Rc<VelloParagraph>, _> = paragraph.clone().try_into();
+            let Ok(paragraph) = paragraph else {
+                warn!("Skipping a paragraph that wasn't laid out for Vello, resolve it with `Paragraph::resolve` first");
+                return
+            };
 
             paragraph.append_to(scene, Some(
-                Affine::scale(scale as _)
+                transform
                 * Affine::translate((position.x as f64, position.y as f64))
             ));
         },
-        Object::Image { image, rect } => {
-            let blob = vello::peniko::Blob::new(Arc::new(image.pixels()));
+        Object::Image { image, rect, source, fit } => {
+            let v_image = vello::peniko::Brush::Image(data.get_or_load_image(image));
             let Dimensions { width, height } = *image.dimensions();
-            let v_image = vello::peniko::Image::new(blob, vello::peniko::ImageFormat::Rgba8, width, height);
 
-            let (scale_x, scale_y) = (rect.width as f64 / width as f64, rect.height as f64 / height as f64);
+            let source = source.unwrap_or(Object::rect(0.0, 0.0, width as f32, height as f32));
+            let (dest, visible) = fit.place((source.width, source.height), rect);
 
-            scene.draw_image(
-                &v_image,
-                Affine::scale(scale as _)
-                * Affine::translate((rect.x as f64, rect.y as f64))
-                * Affine::scale_non_uniform(scale_x, scale_y)
-            );
+            draw_image_part(scene, &v_image, transform, &source, &dest, &visible);
+        },
+        Object::NinePatch { image, rect, insets, scale } => {
+            let v_image = vello::peniko::Brush::Image(data.get_or_load_image(image));
+
+            for (source, dest) in insets.patches(*image.dimensions(), rect, *scale) {
+                draw_image_part(scene, &v_image, transform, &source, &dest, &dest);
+            }
         },
-        Object::Svg { svg, color, rect } => {
+        Object::Svg { svg, brush, rect, fit } => {
             let svg =  vello_svg::usvg::Tree::from_data(
                 &svg.bytes(),
                 &vello_svg::usvg::Options {
@@ -117,6 +154,17 @@ pub(crate) fn draw_object(_renderer: &VelloRenderer, data: &VelloRendererData, s
             let (svg_width, svg_height) = (svg.size().width(), svg.size().height());
             let v_rect = vello::kurbo::Rect::from_origin_size((0.0, 0.0), (svg_width as _, svg_height as _));
 
+            let (Rect { x, y, width, height }, visible) = fit.place((svg_width, svg_height), rect);
+            if visible.is_empty() { return }
+
+            let placement = Affine::translate((x as f64, y as f64))
+            * Affine::scale_non_uniform((width / svg_width) as _, (height / svg_height) as _);
+
+            // Only the part inside of the rect is drawn, in the SVG's coordinates
+            let v_rect = placement.inverse()
+            .transform_rect_bbox(vello_rect(&visible))
+            .intersect(v_rect);
+
 
             svg_scene.push_layer(
                 vello::peniko::BlendMode::new(vello::peniko::Mix::Normal, vello::peniko::Compose::SrcOver),
@@ -137,23 +185,171 @@ pub(crate) fn draw_object(_renderer: &VelloRenderer, data: &VelloRendererData, s
                 svg_scene.fill(
                     Fill::NonZero,
                     Affine::IDENTITY,
-                    &color_from_rgba(*color),
-                    None, &v_rect
+                    &vello_brush(data, brush),
+                    // The brush is in object coordinates, not in the SVG's
+                    Some(placement.inverse() * brush_transform(brush).unwrap_or(Affine::IDENTITY)),
+                    &v_rect
                 );
                 svg_scene.pop_layer();
         
             svg_scene.pop_layer();
         
-            let (scale_x, scale_y) = (rect.width as f32 / svg_width, rect.height as f32 / svg_height);
             scene.append(&svg_scene, Some(
-                Affine::scale(scale as _)
-                * Affine::translate((rect.x as f64, rect.y as f64))
-                * Affine::scale_non_uniform(scale_x as _, scale_y as _)
+                transform
+                * placement
             ));
         },
+        Object::Path { path, brush, stroke } => {
+            let bez_path = vello_path(path);
+
+            scene.fill(
+                vello_fill(path.fill_rule),
+                transform,
+                &vello_brush(data, brush),
+                brush_transform(brush),
+                &bez_path
+            );
+
+            if let Some(stroke) = stroke {
+                stroke_shape(data, scene, stroke, transform, &bez_path);
+            }
+        },
+        Object::Group { transform: group_transform, children } => {
+            let transform = transform * vello_affine(group_transform);
+
+            for child in children {
+                draw_transformed(data, scene, child, transform, window_id, backdrop.as_deref_mut());
+            }
+        },
+        Object::Clip { shape, children } => {
+            let clip = vello::peniko::BlendMode::from(Mix::Clip);
+
+            match shape {
+                ClipShape::Rect(rect) => scene.push_layer(clip, 1.0, transform, &vello_rect(rect)),
+                ClipShape::RoundedRect(rect, rounding) => scene.push_layer(clip, 1.0, transform, &vello_rounded_rect(rect, Some(rounding))),
+                ClipShape::Path(path) => scene.push_layer(clip, 1.0, transform, &vello_path(path))
+            }
+
+            if let Some(backdrop) = backdrop.as_deref_mut() { backdrop.depth += 1; }
+
+            for child in children {
+                draw_transformed(data, scene, child, transform, window_id, backdrop.as_deref_mut());
+            }
+
+            scene.pop_layer();
+            if let Some(backdrop) = backdrop.as_deref_mut() { backdrop.depth -= 1; }
+        },
+        Object::Layer { opacity, blend, children } => {
+            // Layers always need a clip, so use one that is larger than any render target
+            let unbounded = vello::kurbo::Rect::new(-1e6, -1e6, 1e6, 1e6);
+
+            scene.push_layer(
+                vello::peniko::BlendMode::new(vello_mix(*blend), vello::peniko::Compose::SrcOver),
+                *opacity,
+                Affine::IDENTITY,
+                &unbounded
+            );
+
+            if let Some(backdrop) = backdrop.as_deref_mut() { backdrop.depth += 1; }
+
+            for child in children {
+                draw_transformed(data, scene, child, transform, window_id, backdrop.as_deref_mut());
+            }
+
+            scene.pop_layer();
+            if let Some(backdrop) = backdrop.as_deref_mut() { backdrop.depth -= 1; }
+        },
+        Object::Tagged { object, .. } => draw_transformed(data, scene, object, transform, window_id, backdrop),
+        Object::BackdropBlur { rect, rounding, blur, tint } => {
+            let shape = vello_rounded_rect(rect, rounding.as_ref());
+
+            if let Some(backdrop) = backdrop {
+                draw_backdrop_blur(scene, backdrop, &shape, *blur, transform);
+            }
+
+            if let Some(tint) = tint {
+                scene.fill(
+                    Fill::NonZero,
+                    transform,
+                    &vello_brush(data, tint),
+                    brush_transform(tint),
+                    &shape
+                );
+            }
+        }
+    }
+}
+
+/// Renders the scene so far, blurs the area around the shape on the CPU and fills the shape with the result
+fn draw_backdrop_blur(scene: &mut Scene, backdrop: &mut Backdrop, shape: &RoundedRect, blur: f32, transform: Affine) {
+    let sigma = blur as f64 * transform.determinant().abs().sqrt();
+    if sigma <= 0.0 { return }
+    let outset = (sigma * 3.0).ceil();
+
+    // Pixels around the shape are needed as well, so they can be blurred into it
+    let Dimensions { width, height } = backdrop.dimensions;
+    let bounds = transform.transform_rect_bbox(shape.rect())
+    .inflate(outset, outset)
+    .expand()
+    .intersect(kurbo::Rect::new(0.0, 0.0, width as _, height as _));
+    if bounds.is_zero_area() { return }
+
+    // Layers that are still open would be missing otherwise
+    let mut closed = scene.clone();
+    for _ in 0..backdrop.depth {
+        closed.pop_layer();
+    }
+
+    let pixels = match (backdrop.read_back)(&closed) {
+        Ok(pixels) => pixels,
+        Err(err) => {
+            warn!("Couldn't read back the scene for a backdrop blur: {err}");
+            return
+        }
+    };
+
+    let (x, y) = (bounds.x0 as usize, bounds.y0 as usize);
+    let (region_width, region_height) = (bounds.width() as usize, bounds.height() as usize);
+
+    let mut region = Vec::with_capacity(region_width * region_height * 4);
+    for row in y..y + region_height {
+        let start = (row * width as usize + x) * 4;
+        region.extend_from_slice(&pixels[start..start + region_width * 4]);
     }
 
+    // The blur needs premultiplied colors, while Vello uses straight ones
+    for pixel in region.chunks_exact_mut(4) {
+        let alpha = pixel[3] as u32;
+        for channel in &mut pixel[..3] {
+            *channel = ((*channel as u32 * alpha + 127) / 255) as u8;
+        }
+    }
+
+    blur::gaussian_blur(&mut region, region_width, region_height, 4, sigma as f32);
+
+    for pixel in region.chunks_exact_mut(4) {
+        let alpha = pixel[3] as u32;
+        if alpha == 0 { continue }
+        for channel in &mut pixel[..3] {
+            *channel = ((*channel as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+        }
+    }
 
+    let image = vello::peniko::Image::new(
+        vello::peniko::Blob::new(Arc::new(region)),
+        vello::peniko::ImageFormat::Rgba8,
+        region_width as _,
+        region_height as _
+    );
+
+    scene.fill(
+        Fill::NonZero,
+        transform,
+        &vello::peniko::Brush::Image(image),
+        // The image is in physical pixels, while the brush is in object coordinates
+        Some(transform.inverse() * Affine::translate((x as f64, y as f64))),
+        shape
+    );
 }
 
 pub fn color_from_rgba(rgba: u32) -> Color {
@@ -161,6 +357,122 @@ pub fn color_from_rgba(rgba: u32) -> Color {
     Color::from_rgba8(r as u8, g as u8, b as u8, a as u8)
 }
 
+pub(crate) fn vello_brush(data: &VelloRendererData, brush: &Brush) -> vello::peniko::Brush {
+    match brush {
+        Brush::Solid(color) => color_from_rgba(*color).into(),
+        Brush::Gradient(gradient) => {
+            let vello_gradient = match gradient.kind {
+                GradientKind::Linear { start, end } => vello::peniko::Gradient::new_linear(
+                    (start.0 as f64, start.1 as f64),
+                    (end.0 as f64, end.1 as f64)
+                ),
+                GradientKind::Radial { center, radius } => vello::peniko::Gradient::new_radial(
+                    (center.0 as f64, center.1 as f64),
+                    radius
+                ),
+                GradientKind::Sweep { center, start_angle, end_angle } => vello::peniko::Gradient::new_sweep(
+                    (center.0 as f64, center.1 as f64),
+                    start_angle,
+                    end_angle
+                )
+            };
+
+            let stops: Vec<(f32, Color)> = gradient.stops
+            .iter()
+            .map(|stop| (stop.offset, color_from_rgba(stop.color)))
+            .collect();
+
+            vello_gradient
+            .with_stops(stops.as_slice())
+            .with_extend(match gradient.extend {
+                Extend::Pad => vello::peniko::Extend::Pad,
+                Extend::Repeat => vello::peniko::Extend::Repeat,
+                Extend::Reflect => vello::peniko::Extend::Reflect
+            })
+            .into()
+        },
+        Brush::Image(pattern) => {
+            let image = data.get_or_load_image(&pattern.image)
+            .with_extend(vello::peniko::Extend::Repeat);
+
+            vello::peniko::Brush::Image(image)
+        }
+    }
+}
+
+/// The placement of image patterns, other brushes don't need a transform.
+pub(crate) fn brush_transform(brush: &Brush) -> Option<Affine> {
+    match brush {
+        Brush::Image(pattern) => Some(
+            Affine::translate((pattern.x as f64, pattern.y as f64))
+            * Affine::scale(pattern.scale as _)
+        ),
+        _ => None
+    }
+}
+
+/// Fills `visible` with the `source` part of the image, stretched to `dest`.
+fn draw_image_part(scene: &mut Scene, image: &vello::peniko::Brush, transform: Affine, source: &Rect, dest: &Rect, visible: &Rect) {
+    let Rect { x: src_x, y: src_y, width: src_width, height: src_height } = *source;
+    let Rect { x: dst_x, y: dst_y, width: dst_width, height: dst_height } = *dest;
+    let Rect { x: visible_x, y: visible_y, width: visible_width, height: visible_height } = *visible;
+    if source.is_empty() || visible.is_empty() { return }
+
+    scene.fill(
+        Fill::NonZero,
+        transform,
+        image,
+        Some(
+            Affine::translate((dst_x as f64, dst_y as f64))
+            * Affine::scale_non_uniform((dst_width / src_width) as _, (dst_height / src_height) as _)
+            * Affine::translate((-src_x as f64, -src_y as f64))
+        ),
+        &kurbo::Rect::from_origin_size((visible_x as f64, visible_y as f64), (visible_width as f64, visible_height as f64))
+    );
+}
+
+pub(crate) fn vello_image(image: &CacheableImage) -> vello::peniko::Image {
+    let blob = vello::peniko::Blob::new(Arc::new(image.pixels()));
+    let Dimensions { width, height } = *image.dimensions();
+
+    vello::peniko::Image::new(blob, vello::peniko::ImageFormat::Rgba8, width, height)
+}
+
+pub(crate) fn vello_affine(transform: &Transform) -> Affine {
+    let Transform { sx, ky, kx, sy, tx, ty } = *transform;
+    Affine::new([sx, ky, kx, sy, tx, ty].map(f64::from))
+}
+
+fn vello_mix(blend: BlendMode) -> Mix {
+    match blend {
+        BlendMode::Normal => Mix::Normal,
+        BlendMode::Multiply => Mix::Multiply,
+        BlendMode::Screen => Mix::Screen,
+        BlendMode::Overlay => Mix::Overlay,
+        BlendMode::Darken => Mix::Darken,
+        BlendMode::Lighten => Mix::Lighten,
+        BlendMode::ColorDodge => Mix::ColorDodge,
+        BlendMode::ColorBurn => Mix::ColorBurn,
+        BlendMode::HardLight => Mix::HardLight,
+        BlendMode::SoftLight => Mix::SoftLight,
+        BlendMode::Difference => Mix::Difference,
+        BlendMode::Exclusion => Mix::Exclusion,
+        BlendMode::Hue => Mix::Hue,
+        BlendMode::Saturation => Mix::Saturation,
+        BlendMode::Color => Mix::Color,
+        BlendMode::Luminosity => Mix::Luminosity
+    }
+}
+
+fn vello_rounded_rect(rect: &Rect, rounding: Option<&Rounding>) -> RoundedRect {
+    let radii = rounding.map_or(
+        RoundedRectRadii::from_single_radius(0.0),
+        |r| RoundedRectRadii::new(r.top_l as _, r.top_r as _, r.bottom_r as _, r.bottom_l as _)
+    );
+
+    RoundedRect::from_rect(vello_rect(rect), radii)
+}
+
 fn vello_rect(rect: &Rect) -> vello::kurbo::Rect {
     vello::kurbo::Rect::new(
         rect.x as f64,
@@ -168,4 +480,80 @@ fn vello_rect(rect: &Rect) -> vello::kurbo::Rect {
         rect.x as f64 + rect.width as f64,
         rect.y as f64 + rect.height as f64
     )
+}
+
+fn draw_shadow(scene: &mut Scene, rect: &Rect, rounding: Option<&Rounding>, shadow: &Shadow, transform: Affine) {
+    let ((x, y, width, height), radii) = shadow.shape(rect, rounding);
+    let shadow_rect = kurbo::Rect::from_origin_size((x as f64, y as f64), (width as f64, height as f64));
+    let color = color_from_rgba(shadow.color);
+
+    if shadow.blur > 0.0 {
+        // Vello only supports a single radius for blurred rects
+        let radius = radii.into_iter().fold(0.0, f32::max);
+        scene.draw_blurred_rounded_rect(transform, shadow_rect, color, radius as _, (shadow.blur / 2.0) as _);
+    } else {
+        let [top_l, top_r, bottom_r, bottom_l] = radii.map(f64::from);
+
+        scene.fill(
+            Fill::NonZero,
+            transform,
+            color,
+            None,
+            &RoundedRect::from_rect(shadow_rect, RoundedRectRadii::new(top_l, top_r, bottom_r, bottom_l))
+        );
+    }
+}
+
+fn stroke_shape(data: &VelloRendererData, scene: &mut Scene, stroke: &Stroke, transform: Affine, shape: &impl Shape) {
+    let mut vello_stroke = kurbo::Stroke::new(stroke.width as _)
+    .with_caps(match stroke.cap {
+        LineCap::Butt => Cap::Butt,
+        LineCap::Round => Cap::Round,
+        LineCap::Square => Cap::Square
+    })
+    .with_join(match stroke.join {
+        LineJoin::Miter => Join::Miter,
+        LineJoin::Round => Join::Round,
+        LineJoin::Bevel => Join::Bevel
+    })
+    .with_miter_limit(stroke.miter_limit as _);
+
+    if let Some(dash) = stroke.dash_pattern() {
+        vello_stroke = vello_stroke.with_dashes(stroke.dash_offset as _, dash.into_iter().map(f64::from));
+    }
+
+    scene.stroke(
+        &vello_stroke,
+        transform,
+        &vello_brush(data, &stroke.brush),
+        brush_transform(&stroke.brush),
+        shape
+    );
+}
+
+fn vello_path(path: &Path) -> BezPath {
+    let mut bez_path = BezPath::new();
+
+    for element in path.elements() {
+        match element {
+            PathElement::MoveTo(x, y) => bez_path.move_to((x as f64, y as f64)),
+            PathElement::LineTo(x, y) => bez_path.line_to((x as f64, y as f64)),
+            PathElement::QuadTo(cx, cy, x, y) => bez_path.quad_to((cx as f64, cy as f64), (x as f64, y as f64)),
+            PathElement::CubicTo(cx1, cy1, cx2, cy2, x, y) => bez_path.curve_to(
+                (cx1 as f64, cy1 as f64),
+                (cx2 as f64, cy2 as f64),
+                (x as f64, y as f64)
+            ),
+            PathElement::Close => bez_path.close_path()
+        }
+    }
+
+    bez_path
+}
+
+fn vello_fill(fill_rule: FillRule) -> Fill {
+    match fill_rule {
+        FillRule::NonZero => Fill::NonZero,
+        FillRule::EvenOdd => Fill::EvenOdd
+    }
 }
\ No newline at end of file
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+
+
+#[derive(Debug, Error)]
+pub enum VelloRendererError {
+    #[error("No wgpu device available")]
+    NoDevice,
+    #[error("Failed to render scene: {0}")]
+    Render(vello::Error),
+    #[error("Failed to read back rendered texture: {0}")]
+    Readback(vello::wgpu::BufferAsyncError)
+}
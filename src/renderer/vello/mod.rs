@@ -1,21 +1,27 @@
 use std::{cell::{Cell, RefCell}, fmt::Debug};
 
-use vello::{peniko::color::AlphaColor, util::{RenderContext, RenderSurface}, AaConfig, Scene};
+use log::warn;
+use vello::{peniko::{color::AlphaColor, Color}, util::{RenderContext, RenderSurface}, wgpu, AaConfig, Scene};
 
-use crate::{backend::renderer_data::vello::VelloRendererData, traits::RendererDataTrait, types::{Dimensions, Object, RResult, RendererData, Window, WindowTrait}};
+use crate::{backend::renderer_data::vello::VelloRendererData, traits::RendererDataTrait, types::{CacheableImage, Dimensions, Object, PixelFormat, RResult, RendererData, RendererError, Window, WindowTrait}};
 
-use super::RendererTrait;
+use self::{adapter::Backdrop, errors::VelloRendererError};
+
+use super::{overlay::DebugOverlay, RendererTrait, DEFAULT_CLEAR_COLOR};
 
 
 pub mod text;
 pub mod adapter;
+pub mod errors;
 
 
 pub struct VelloRenderer {
     scene: RefCell<Scene>,
     surface: RefCell<RenderSurface<'static>>,
     renderer: RefCell<vello::Renderer>,
-    temp_context: Cell<Option<RenderContext>>
+    temp_context: Cell<Option<RenderContext>>,
+    clear_color: Cell<u32>,
+    overlay: DebugOverlay
 }
 
 impl VelloRenderer {
@@ -38,7 +44,18 @@ impl VelloRenderer {
             height,
             vello::wgpu::PresentMode::Fifo
         );
-        let surface = pollster::block_on(surface_future).unwrap();
+        let mut surface = pollster::block_on(surface_future).unwrap();
+
+        // Vello's output isn't premultiplied, so only the alpha mode that doesn't expect it can show what is behind the window.
+        // Any other mode would either draw transparent clear colors as opaque or brighten the edges of transparent pixels.
+        let device_handle = &render_context.devices[surface.dev_id];
+        let capabilities = surface.surface.get_capabilities(device_handle.adapter());
+        if capabilities.alpha_modes.contains(&wgpu::CompositeAlphaMode::PostMultiplied) {
+            surface.config.alpha_mode = wgpu::CompositeAlphaMode::PostMultiplied;
+            surface.surface.configure(&device_handle.device, &surface.config);
+        } else {
+            warn!("The surface doesn't support straight alpha, so transparent windows are drawn opaque with Vello");
+        }
 
         let renderer = RefCell::new(Self::create_vello_renderer(render_context, &surface));
 
@@ -46,7 +63,9 @@ impl VelloRenderer {
             scene,
             renderer,
             surface: unsafe { std::mem::transmute(RefCell::new(surface)) },
-            temp_context: Cell::new(temp_context)
+            temp_context: Cell::new(temp_context),
+            clear_color: Cell::new(DEFAULT_CLEAR_COLOR),
+            overlay: DebugOverlay::new()
         })
     }
 
@@ -60,6 +79,22 @@ impl VelloRenderer {
 }
 
 impl RendererTrait for VelloRenderer {
+    fn set_clear_color(&self, color: u32) {
+        self.clear_color.set(color);
+    }
+
+    fn clear_color(&self) -> u32 {
+        self.clear_color.get()
+    }
+
+    fn set_debug_overlay(&self, enabled: bool) {
+        self.overlay.set_enabled(enabled);
+    }
+
+    fn debug_overlay(&self) -> bool {
+        self.overlay.is_enabled()
+    }
+
     fn render(&self, window: &Window, data: &RendererData, objects: Vec<&Object>) -> RResult<()> {
         let mut scene = self.scene.borrow_mut();
         scene.reset();
@@ -71,9 +106,25 @@ impl RendererTrait for VelloRenderer {
         let data = data.try_as_vello_ref().unwrap();
         let scale = window.current_scale();
         let window_id = window.id();
+        let base_color = adapter::color_from_rgba(self.clear_color.get());
 
-        for object in objects {
-            adapter::draw_object(self, data, &mut scene, object, scale, &window_id);
+        let mut read_back = |scene: &Scene| render_to_pixels(
+            &device_handle.device,
+            &device_handle.queue,
+            &mut self.renderer.borrow_mut(),
+            scene,
+            Dimensions { width, height },
+            base_color
+        );
+        let mut backdrop = Backdrop::new(&mut read_back, Dimensions { width, height });
+
+        for object in &objects {
+            adapter::draw_object(data, &mut scene, object, scale, Some(&window_id), Some(&mut backdrop));
+        }
+
+        let overlay = self.overlay.objects(&objects, !data.fonts.borrow().is_empty(), |object| adapter::object_bounds(data, object));
+        for object in &overlay {
+            adapter::draw_object(data, &mut scene, object, scale, Some(&window_id), Some(&mut backdrop));
         }
 
         self.renderer.borrow_mut()
@@ -83,7 +134,7 @@ impl RendererTrait for VelloRenderer {
             &scene,
             &self.surface.borrow().target_view,
             &vello::RenderParams {
-                base_color: AlphaColor::new([0.1, 0.1, 0.1, 1.0]), // Background color
+                base_color,
                 width,
                 height,
                 antialiasing_method: AaConfig::Area,
@@ -156,6 +207,132 @@ impl RendererTrait for VelloRenderer {
 }
 
 
+/// Renders the objects into an image, without needing a window or surface.
+pub(crate) fn render_offscreen(data: &VelloRendererData, objects: Vec<&Object>, dimensions: Dimensions<u32>, scale: f32) -> RResult<CacheableImage> {
+    let Dimensions { width, height } = dimensions;
+    if width == 0 || height == 0 { return Err(RendererError::ZeroSize) }
+
+    let mut context = data.context.borrow_mut();
+    let dev_id = pollster::block_on(context.device(None))
+    .ok_or(RendererError::Vello(VelloRendererError::NoDevice))?;
+    let device_handle = &context.devices[dev_id];
+
+    let mut offscreen_renderer = data.offscreen_renderer.borrow_mut();
+    if offscreen_renderer.is_none() {
+        *offscreen_renderer = Some(
+            vello::Renderer::new(&device_handle.device, vello::RendererOptions::default())
+            .map_err(|err| RendererError::Vello(VelloRendererError::Render(err)))?
+        );
+    }
+    let renderer = offscreen_renderer.as_mut().unwrap();
+
+    let mut scene = Scene::new();
+    {
+        let mut read_back = |scene: &Scene| render_to_pixels(
+            &device_handle.device,
+            &device_handle.queue,
+            renderer,
+            scene,
+            dimensions,
+            AlphaColor::TRANSPARENT
+        );
+        let mut backdrop = Backdrop::new(&mut read_back, dimensions);
+
+        for object in objects {
+            adapter::draw_object(data, &mut scene, object, scale, None, Some(&mut backdrop));
+        }
+    }
+
+    let pixels = render_to_pixels(
+        &device_handle.device,
+        &device_handle.queue,
+        renderer,
+        &scene,
+        dimensions,
+        AlphaColor::TRANSPARENT
+    )?;
+
+    Ok(CacheableImage::new(pixels.into(), PixelFormat::RGBA8, dimensions))
+}
+
+/// Renders the scene into a new texture and copies its (non-premultiplied) RGBA8 pixels back to the CPU.
+pub(crate) fn render_to_pixels(device: &wgpu::Device, queue: &wgpu::Queue, renderer: &mut vello::Renderer, scene: &Scene, dimensions: Dimensions<u32>, base_color: Color) -> RResult<Vec<u8>> {
+    let Dimensions { width, height } = dimensions;
+    let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Offscreen Target"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+
+    renderer.render_to_texture(
+        device,
+        queue,
+        scene,
+        &texture.create_view(&wgpu::TextureViewDescriptor::default()),
+        &vello::RenderParams {
+            base_color,
+            width,
+            height,
+            antialiasing_method: AaConfig::Area,
+        },
+    ).map_err(|err| RendererError::Vello(VelloRendererError::Render(err)))?;
+
+    // Rows of a texture copy need to be aligned to 256 bytes
+    let row_bytes = width * 4;
+    let padded_row_bytes = row_bytes.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Offscreen Readback"),
+        size: padded_row_bytes as u64 * height as u64,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Offscreen Copy"),
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row_bytes),
+                rows_per_image: None,
+            },
+        },
+        size,
+    );
+    queue.submit([encoder.finish()]);
+
+    let slice = buffer.slice(..);
+    let (sender, receiver) = crossbeam_channel::bounded(1);
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        sender.send(result).ok();
+    });
+    device.poll(wgpu::Maintain::Wait);
+
+    receiver.recv()
+    .expect("Buffer mapping callback was dropped")
+    .map_err(|err| RendererError::Vello(VelloRendererError::Readback(err)))?;
+
+    let mut pixels = Vec::with_capacity(row_bytes as usize * height as usize);
+    for row in slice.get_mapped_range().chunks(padded_row_bytes as usize) {
+        pixels.extend_from_slice(&row[..row_bytes as usize]);
+    }
+    buffer.unmap();
+
+    Ok(pixels)
+}
+
+
 impl Debug for VelloRenderer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("VelloRenderer")
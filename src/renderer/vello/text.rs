@@ -1,14 +1,15 @@
 use std::{fmt::Debug, num::NonZeroU32, rc::Rc};
 
-use skrifa::MetadataProvider;
-use text_layout::{Item, ParagraphLayout};
 use vello::{kurbo::Affine, peniko::Fill, Glyph, Scene};
 
-use crate::{backend::renderer_data::vello::VelloRendererData, types::{Dimensions, ParagraphTrait, TextOptions, TextWrap}};
+use crate::{backend::renderer_data::vello::VelloRendererData, renderer::layout, types::{Dimensions, ParagraphTrait, TextOptions, TextSpan}};
 
 #[derive(Clone)]
 pub struct VelloParagraph {
     options: TextOptions,
+    text: String,
+    spans: Vec<TextSpan>,
+    max_height: Option<NonZeroU32>,
     scene: Scene,
     dimensions: Dimensions<f32>
 }
@@ -18,104 +19,60 @@ impl ParagraphTrait for Rc<VelloParagraph> {
         &self.options
     }
 
+    fn text(&self) -> &str {
+        &self.text
+    }
+
+    fn spans(&self) -> &[TextSpan] {
+        &self.spans
+    }
+
+    fn max_height(&self) -> Option<NonZeroU32> {
+        self.max_height
+    }
+
+    fn width(&self) -> f32 {
+        self.dimensions.width
+    }
+
     fn height(&self) -> f32 {
         self.dimensions.height
     }
 }
 
 impl VelloParagraph {
-    pub fn new(data: &VelloRendererData, text: String, width: u32, max_height: Option<NonZeroU32>, options: TextOptions) -> Self {
-        let font = data.get_font(&options.font).expect("No Font available!");
-
-        let size = options.size;
-        let should_layout = options.wrap == TextWrap::WordWrap;
-        
-        let layout: text_layout::KnuthPlass<f32> = text_layout::KnuthPlass::new().with_threshold(f32::INFINITY);
-        let mut layout_items: Option<Vec<Item<()>>> = should_layout.then(|| Vec::with_capacity(text.len()));
-
-        let axes = font.font_ref.axes();
-        let var_loc = axes.location::<&[(&str, f32)]>(&[
-            ("wght", options.weight as f32),
-            ("ital", if options.italic { 1.0 } else { 0.0 }),
-            ("slnt", if options.italic { -45.0 } else { 0.0 }) // TODO: Test this
-        ]);
-
-        let font_size = skrifa::instance::Size::new(size);
-        let charmap = font.font_ref.charmap();
-        let metrics = font.font_ref.metrics(font_size, &var_loc);
-        let line_height = metrics.ascent - metrics.descent + metrics.leading;
-        let glyph_metrics = font.font_ref.glyph_metrics(font_size, &var_loc);
-
-        let glyphs: Vec<_> = text.chars().map(|ch| {
-            let gid = charmap.map(ch).unwrap_or_default();
-            let advance = glyph_metrics.advance_width(gid).unwrap_or_default();
-
-            if let Some(items) = &mut layout_items {
-                items.push(if ch.is_whitespace() && items.len() != 0 {
-                    Item::Glue {
-                        width: advance,
-                        stretch: 1.0,
-                        shrink: 0.0,
-                        data: (),
-                    }
-                } else {
-                    Item::Box {
-                        width: advance,
-                        data: (),
-                    }
-                });
-            }
-
-            (gid, advance)
-        }).collect();
-
-
-        let breaks = if let Some(items) = layout_items {
-            layout.layout_paragraph(items.as_slice(),  width as f32)
-        } else {
-            Vec::new()
-        };
-
-                
+    pub fn new(data: &VelloRendererData, spans: Vec<TextSpan>, width: u32, max_height: Option<NonZeroU32>, options: TextOptions) -> Self {
+        let fonts: Vec<_> = spans.iter().map(|span| data.get_font(&span.style.font).expect("No Font available!")).collect();
+        let layout_spans: Vec<_> = fonts.iter().zip(&spans).map(|(font, span)| (&font.font_ref, span)).collect();
+
+        let layout = layout::layout_paragraph(&layout_spans, width, max_height, &options);
+
         let mut scene = vello::Scene::new();
-        let mut dimensions = Dimensions::new(width as _, line_height);
-
-        let start_x = 0.0;
-        let mut pen_x = start_x;
-        let mut pen_y = 0.0 + line_height;
-        let mut break_i = 0;
-
-        scene.draw_glyphs(&font.font)
-        .hint(true)
-        .font_size(size)
-        .brush(super::adapter::color_from_rgba(options.color))
-        .normalized_coords(bytemuck::cast_slice(var_loc.coords()))
-        .draw(Fill::NonZero, glyphs.iter().enumerate().map(|(index, (id, mut advance))| {
-            if breaks.len() > break_i && index == breaks[break_i].break_at {
-                break_i += 1;
-
-                pen_y += line_height; // TODO: Handle max_height
-                pen_x = start_x;
-                advance = 0.0;
-
-                dimensions.height += line_height;
-            }
-
-            let x = pen_x;
-            pen_x += advance;
-             
-            Glyph {
-                id: id.to_u32(),
-                x,
-                y: pen_y
-            }
-        }));
 
+        for ((font, span), run) in fonts.iter().zip(&spans).zip(&layout.runs) {
+            scene.draw_glyphs(&font.font)
+            .hint(true)
+            .font_size(run.size)
+            .brush(&super::adapter::vello_brush(data, &span.style.brush))
+            .normalized_coords(bytemuck::cast_slice(run.location.coords()))
+            .draw(Fill::NonZero, run.glyphs.iter().map(|glyph| Glyph {
+                id: glyph.id,
+                x: glyph.x,
+                y: glyph.y
+            }));
+        }
+
+        for decoration in &layout.decorations {
+            super::adapter::draw_object(data, &mut scene, decoration, 1.0, None, None);
+        }
 
         Self {
             options,
+            text: spans.iter().map(|span| span.text.as_str()).collect(),
+            spans,
+            max_height,
             scene,
-            dimensions
+            dimensions: layout.dimensions
         }
     }
 
@@ -129,6 +86,7 @@ impl Debug for VelloParagraph {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("VelloParagraph")
         .field("options", &self.options)
+        .field("text", &self.text)
         .finish_non_exhaustive()
     }
 }
\ No newline at end of file
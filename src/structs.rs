@@ -4,6 +4,7 @@ use num_traits::AsPrimitive;
 
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Dimensions<T> {
     pub width: T,
     pub height: T
@@ -43,6 +44,7 @@ impl<T: AsPrimitive<f32>> Div<f32> for Dimensions<T> where f32: AsPrimitive<T> {
 
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position<T> {
     pub x: T,
     pub y: T